@@ -12,8 +12,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::borrow::Cow;
-
 use risingwave_common::catalog::{ColumnDesc, ColumnId};
 use risingwave_common::types::DataType;
 use risingwave_pb::plan::ColumnCatalog as ProstColumnCatalog;
@@ -63,7 +61,11 @@ impl ColumnCatalog {
         }
     }
 
-    /// Generate incremental `column_id` for every `column_desc` and `column_desc.field_descs`
+    /// Generate incremental `column_id` for every `column_desc` and `column_desc.field_descs`.
+    ///
+    /// Ids are assigned depth-first in column order starting from 0, regardless of the ids the
+    /// columns had before, so the result only depends on the shape of the columns and applying it
+    /// again is a no-op.
     pub fn generate_increment_id(catalogs: &mut Vec<ColumnCatalog>) {
         let mut index = 0;
         for catalog in catalogs {
@@ -81,12 +83,51 @@ impl From<ProstColumnCatalog> for ColumnCatalog {
     }
 }
 
-impl ColumnCatalog {
-    pub fn name_with_hidden(&self) -> Cow<str> {
-        if self.is_hidden {
-            Cow::Owned(format!("{}(hidden)", self.column_desc.name))
-        } else {
-            Cow::Borrowed(&self.column_desc.name)
-        }
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{ColumnDesc, ColumnId};
+    use risingwave_common::types::DataType;
+
+    use super::ColumnCatalog;
+
+    #[test]
+    fn test_generate_increment_id_idempotent() {
+        let field = |name: &str| ColumnDesc {
+            name: name.to_string(),
+            ..ColumnDesc::unnamed(ColumnId::new(42), DataType::Varchar)
+        };
+        let mut columns = vec![
+            ColumnCatalog::row_id_column(),
+            ColumnCatalog {
+                column_desc: ColumnDesc {
+                    data_type: DataType::Struct {
+                        fields: vec![DataType::Varchar, DataType::Varchar].into(),
+                    },
+                    column_id: ColumnId::new(7),
+                    name: "country".to_string(),
+                    field_descs: vec![field("country.address"), field("country.zipcode")],
+                    type_name: ".test.Country".to_string(),
+                },
+                is_hidden: false,
+            },
+            ColumnCatalog {
+                column_desc: field("v1"),
+                is_hidden: true,
+            },
+        ];
+
+        ColumnCatalog::generate_increment_id(&mut columns);
+        let ids = |columns: &[ColumnCatalog]| {
+            columns
+                .iter()
+                .flat_map(|c| c.column_desc.get_column_descs())
+                .map(|c| c.column_id.get_id())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&columns), vec![0, 1, 2, 3, 4]);
+
+        let once = columns.clone();
+        ColumnCatalog::generate_increment_id(&mut columns);
+        assert_eq!(columns, once);
     }
 }
@@ -32,6 +32,24 @@ pub struct TableCatalog {
     pub name: String,
     pub columns: Vec<ColumnCatalog>,
     pub pk_desc: Vec<OrderedColumnDesc>,
+    /// Whether the table is a mirror maintained alongside a primary materialized view, sharing its
+    /// input but distributed by a different key.
+    pub is_mirror: bool,
+    /// The number of vnodes the table is initially distributed over, if specified by the user,
+    /// e.g. to align with the partitions of the upstream source.
+    pub vnode_count: Option<u32>,
+    /// The description of the table given by the user.
+    pub description: Option<String>,
+    /// The rate limit of backfilling the table, in rows per second.
+    pub backfill_rate_limit: Option<u32>,
+    /// The upstream relations of the table in the order they should be backfilled. Empty if the
+    /// user does not specify one.
+    pub backfill_order: Vec<TableId>,
+    /// The number of leading pk columns that reads scan by as a prefix, if the user narrows it
+    /// down from the whole pk.
+    pub read_prefix_len_hint: Option<u32>,
+    /// Whether the table only receives inserts.
+    pub append_only: bool,
 }
 
 impl TableCatalog {
@@ -70,6 +88,78 @@ impl TableCatalog {
         self.name.as_ref()
     }
 
+    /// Get the table catalog's is mirror.
+    pub fn is_mirror(&self) -> bool {
+        self.is_mirror
+    }
+
+    /// Assert that the stream key, given as column indices of the table, is a subset of the pk.
+    /// Vnodes are computed from pk columns, so a stream key column outside the pk would route
+    /// updates of the same row to different vnodes.
+    pub fn assert_stream_key_subset_of_pk(&self, stream_key: &[usize]) {
+        let pk_column_ids: HashSet<_> = self
+            .pk_desc
+            .iter()
+            .map(|c| c.column_desc.column_id)
+            .collect();
+        for &idx in stream_key {
+            let column = &self.columns[idx];
+            assert!(
+                pk_column_ids.contains(&column.column_id()),
+                "stream key column {} is not in the pk of table {}",
+                column.name(),
+                self.name
+            );
+        }
+    }
+
+    /// Get the table catalog's vnode count.
+    pub fn vnode_count(&self) -> Option<u32> {
+        self.vnode_count
+    }
+
+    /// Get a reference to the table catalog's description.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Get the table catalog's backfill rate limit.
+    pub fn backfill_rate_limit(&self) -> Option<u32> {
+        self.backfill_rate_limit
+    }
+
+    /// Get a reference to the table catalog's backfill order.
+    pub fn backfill_order(&self) -> &[TableId] {
+        &self.backfill_order
+    }
+
+    /// Get whether the table only receives inserts.
+    pub fn append_only(&self) -> bool {
+        self.append_only
+    }
+
+    /// Get the table catalog's read prefix length hint, if one is given.
+    pub fn read_prefix_len_hint(&self) -> Option<u32> {
+        self.read_prefix_len_hint
+    }
+
+    /// Get the number of leading pk columns that reads scan by as a prefix, which is the whole pk
+    /// unless a read prefix length hint is given.
+    pub fn effective_read_prefix_len(&self) -> usize {
+        self.read_prefix_len_hint
+            .map_or(self.pk_desc.len(), |len| len as usize)
+    }
+
+    /// Like [`Self::to_prost`], but with the columns sorted by their ids, so that catalogs that
+    /// only differ in the order of their columns serialize the same, e.g. to key a plan cache.
+    pub fn to_canonical_prost(&self, schema_id: SchemaId, database_id: DatabaseId) -> ProstTable {
+        let mut table = self.to_prost(schema_id, database_id);
+        table
+            .columns
+            .sort_by_key(|c| c.column_desc.as_ref().unwrap().column_id);
+        table
+    }
+
     pub fn to_prost(&self, schema_id: SchemaId, database_id: DatabaseId) -> ProstTable {
         let (pk_column_ids, pk_orders) = self
             .pk_desc()
@@ -94,6 +184,13 @@ impl TableCatalog {
             optional_associated_source_id: self
                 .associated_source_id
                 .map(|source_id| OptionalAssociatedSourceId::AssociatedSourceId(source_id.into())),
+            is_mirror: self.is_mirror,
+            vnode_count: self.vnode_count.unwrap_or(0),
+            description: self.description.clone().unwrap_or_default(),
+            backfill_rate_limit: self.backfill_rate_limit.unwrap_or(0),
+            backfill_order: self.backfill_order.iter().map(|id| id.table_id).collect(),
+            read_prefix_len_hint: self.read_prefix_len_hint.unwrap_or(0),
+            append_only: self.append_only,
         }
     }
 }
@@ -139,6 +236,13 @@ impl From<ProstTable> for TableCatalog {
             name,
             pk_desc,
             columns,
+            is_mirror: tb.is_mirror,
+            vnode_count: (tb.vnode_count != 0).then(|| tb.vnode_count),
+            description: (!tb.description.is_empty()).then(|| tb.description),
+            backfill_rate_limit: (tb.backfill_rate_limit != 0).then(|| tb.backfill_rate_limit),
+            backfill_order: tb.backfill_order.into_iter().map(TableId::new).collect(),
+            read_prefix_len_hint: (tb.read_prefix_len_hint != 0).then(|| tb.read_prefix_len_hint),
+            append_only: tb.append_only,
         }
     }
 }
@@ -200,6 +304,13 @@ mod tests {
             dependent_relations: vec![],
             optional_associated_source_id: OptionalAssociatedSourceId::AssociatedSourceId(233)
                 .into(),
+            is_mirror: false,
+            vnode_count: 0,
+            description: String::new(),
+            backfill_rate_limit: 0,
+            backfill_order: vec![],
+            read_prefix_len_hint: 0,
+            append_only: false,
         }
         .into();
 
@@ -242,8 +353,91 @@ mod tests {
                 pk_desc: vec![OrderedColumnDesc {
                     column_desc: row_id_column_desc(),
                     order: OrderType::Ascending
-                }]
+                }],
+                is_mirror: false,
+                vnode_count: None,
+                description: None,
+                backfill_rate_limit: None,
+                backfill_order: vec![],
+                read_prefix_len_hint: None,
+                append_only: false,
             }
         );
+        // Without a hint, reads scan by the whole pk.
+        assert_eq!(table.effective_read_prefix_len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "stream key column v1 is not in the pk of table test")]
+    fn test_stream_key_not_in_pk() {
+        let v1 = ColumnDesc::unnamed(ColumnId::new(1), DataType::Int32);
+        let table = TableCatalog {
+            id: TableId::new(0),
+            associated_source_id: None,
+            name: "test".to_string(),
+            columns: vec![
+                ColumnCatalog::row_id_column(),
+                ColumnCatalog {
+                    column_desc: ColumnDesc {
+                        name: "v1".to_string(),
+                        ..v1
+                    },
+                    is_hidden: false,
+                },
+            ],
+            pk_desc: vec![OrderedColumnDesc {
+                column_desc: row_id_column_desc(),
+                order: OrderType::Ascending,
+            }],
+            is_mirror: false,
+            vnode_count: None,
+            description: None,
+            backfill_rate_limit: None,
+            backfill_order: vec![],
+            read_prefix_len_hint: None,
+            append_only: false,
+        };
+        table.assert_stream_key_subset_of_pk(&[0]);
+        table.assert_stream_key_subset_of_pk(&[0, 1]);
+    }
+
+    #[test]
+    fn test_to_canonical_prost() {
+        let v1 = ColumnDesc::unnamed(ColumnId::new(1), DataType::Int32);
+        let table = TableCatalog {
+            id: TableId::new(0),
+            associated_source_id: None,
+            name: "test".to_string(),
+            columns: vec![
+                ColumnCatalog::row_id_column(),
+                ColumnCatalog {
+                    column_desc: ColumnDesc {
+                        name: "v1".to_string(),
+                        ..v1
+                    },
+                    is_hidden: false,
+                },
+            ],
+            pk_desc: vec![OrderedColumnDesc {
+                column_desc: row_id_column_desc(),
+                order: OrderType::Ascending,
+            }],
+            is_mirror: false,
+            vnode_count: None,
+            description: None,
+            backfill_rate_limit: None,
+            backfill_order: vec![],
+            read_prefix_len_hint: None,
+            append_only: false,
+        };
+        let mut reordered = table.clone();
+        reordered.columns.reverse();
+
+        assert_ne!(table.to_prost(0, 0), reordered.to_prost(0, 0));
+        assert_eq!(
+            table.to_canonical_prost(0, 0),
+            reordered.to_canonical_prost(0, 0)
+        );
+        assert_eq!(table.to_canonical_prost(0, 0), table.to_prost(0, 0));
     }
 }
@@ -12,17 +12,165 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use pgwire::pg_response::{PgResponse, StatementType};
+use risingwave_common::error::ErrorCode::InvalidInputSyntax;
 use risingwave_common::error::Result;
 use risingwave_pb::catalog::Table as ProstTable;
-use risingwave_sqlparser::ast::{ObjectName, Query};
+use risingwave_sqlparser::ast::{ObjectName, Query, SqlOption, Value};
 
-use crate::binder::Binder;
+use crate::binder::{Binder, BoundQuery, BoundSetExpr, Relation};
+use crate::catalog::table_catalog::TableCatalog;
+use crate::catalog::TableId;
+use crate::expr::{ExprVisitor, Subquery};
+use crate::optimizer::plan_node::{collect_dependent_table_ids, PlanTreeNodeUnary};
 use crate::optimizer::property::Distribution;
 use crate::optimizer::PlanRef;
 use crate::planner::Planner;
 use crate::session::{OptimizerContext, OptimizerContextRef, SessionImpl};
 
+/// The `WITH` option that lists the upstream relations of a materialized view in the order they
+/// should be backfilled, e.g. `WITH (backfill_order = 'dim_mv, fact_mv')`.
+pub const BACKFILL_ORDER: &str = "backfill_order";
+
+/// Resolves the relations named in `backfill_order` to their ids. Each of them must be read by
+/// `plan` and named only once.
+fn resolve_backfill_order(
+    session: &SessionImpl,
+    schema_name: &str,
+    plan: PlanRef,
+    backfill_order: &str,
+) -> Result<Vec<TableId>> {
+    let dependencies = collect_dependent_table_ids(plan, false);
+    let catalog = session.env().catalog_reader().read_guard();
+    let mut table_ids = vec![];
+    for name in backfill_order.split(',') {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(InvalidInputSyntax(format!(
+                "empty relation name in {} '{}'",
+                BACKFILL_ORDER, backfill_order
+            ))
+            .into());
+        }
+        let table_id = match catalog.get_table_by_name(session.database(), schema_name, name) {
+            Ok(table) => table.id(),
+            Err(_) => TableId::new(
+                catalog
+                    .get_source_by_name(session.database(), schema_name, name)?
+                    .id,
+            ),
+        };
+        if !dependencies.contains(&table_id) {
+            return Err(InvalidInputSyntax(format!(
+                "{} in {} is not an upstream of the materialized view",
+                name, BACKFILL_ORDER
+            ))
+            .into());
+        }
+        if table_ids.contains(&table_id) {
+            return Err(InvalidInputSyntax(format!(
+                "{} is listed more than once in {}",
+                name, BACKFILL_ORDER
+            ))
+            .into());
+        }
+        table_ids.push(table_id);
+    }
+    Ok(table_ids)
+}
+
+/// Collects the ids of the tables and sources a bound query reads, including those read by its
+/// subqueries. Sources are identified by their ids as table ids.
+#[derive(Default)]
+struct BoundRelationCollector {
+    relation_ids: Vec<TableId>,
+}
+
+impl BoundRelationCollector {
+    fn visit_query(&mut self, query: &BoundQuery) {
+        match &query.body {
+            BoundSetExpr::Select(select) => {
+                if let Some(from) = &select.from {
+                    self.visit_relation(from);
+                }
+                select
+                    .select_items
+                    .iter()
+                    .chain(select.where_clause.iter())
+                    .chain(select.group_by.iter())
+                    .for_each(|expr| self.visit_expr(expr));
+            }
+            BoundSetExpr::Values(values) => values
+                .rows
+                .iter()
+                .flatten()
+                .for_each(|expr| self.visit_expr(expr)),
+        }
+    }
+
+    fn visit_relation(&mut self, relation: &Relation) {
+        match relation {
+            Relation::Source(source) => self.relation_ids.push(TableId::new(source.catalog.id)),
+            Relation::BaseTable(table) => self.relation_ids.push(table.table_id),
+            Relation::Subquery(subquery) => self.visit_query(&subquery.query),
+            Relation::Join(join) => {
+                self.visit_relation(&join.left);
+                self.visit_relation(&join.right);
+                self.visit_expr(&join.cond);
+            }
+            Relation::WindowTableFunction(window) => self.visit_relation(&window.input),
+        }
+    }
+}
+
+impl ExprVisitor for BoundRelationCollector {
+    fn visit_subquery(&mut self, subquery: &Subquery) {
+        self.visit_query(&subquery.query);
+    }
+}
+
+/// Called when the name of the materialized view is taken, to tell a definition that reads the
+/// relation of that name, e.g. one written before a rename, from a plain name conflict. A query
+/// that fails to bind is left to the name conflict.
+fn check_self_reference(
+    session: &SessionImpl,
+    schema_name: &str,
+    table_name: &str,
+    query: Query,
+) -> Result<()> {
+    let catalog_reader = session.env().catalog_reader();
+    let relation_id = {
+        let catalog = catalog_reader.read_guard();
+        match catalog.get_table_by_name(session.database(), schema_name, table_name) {
+            Ok(table) => table.id(),
+            Err(_) => match catalog.get_source_by_name(session.database(), schema_name, table_name)
+            {
+                Ok(source) => TableId::new(source.id),
+                Err(_) => return Ok(()),
+            },
+        }
+    };
+    let bound = {
+        let mut binder = Binder::new(catalog_reader.read_guard(), session.database().to_string());
+        match binder.bind_query(query) {
+            Ok(bound) => bound,
+            Err(_) => return Ok(()),
+        }
+    };
+    let mut collector = BoundRelationCollector::default();
+    collector.visit_query(&bound);
+    if collector.relation_ids.contains(&relation_id) {
+        return Err(InvalidInputSyntax(format!(
+            "materialized view {} cannot reference itself",
+            table_name
+        ))
+        .into());
+    }
+    Ok(())
+}
+
 /// Generate create MV plan, return plan and mv table info.
 pub fn gen_create_mv_plan(
     session: &SessionImpl,
@@ -31,11 +179,18 @@ pub fn gen_create_mv_plan(
     name: ObjectName,
 ) -> Result<(PlanRef, ProstTable)> {
     let (schema_name, table_name) = Binder::resolve_table_name(name)?;
-    let (database_id, schema_id) = session
+    let duplicated = session
         .env()
         .catalog_reader()
         .read_guard()
-        .check_relation_name_duplicated(session.database(), &schema_name, &table_name)?;
+        .check_relation_name_duplicated(session.database(), &schema_name, &table_name);
+    let (database_id, schema_id) = match duplicated {
+        Ok(ids) => ids,
+        Err(err) => {
+            check_self_reference(session, &schema_name, &table_name, *query)?;
+            return Err(err);
+        }
+    };
 
     let bound = {
         let mut binder = Binder::new(
@@ -45,36 +200,86 @@ pub fn gen_create_mv_plan(
         binder.bind_query(*query)?
     };
 
-    let mut plan_root = Planner::new(context).plan_query(bound)?;
+    let mut plan_root = Planner::new(context.clone()).plan_query(bound)?;
     plan_root.set_required_dist(Distribution::any().clone());
-    let materialize = plan_root.gen_create_mv_plan(table_name)?;
-    let table = materialize.table().to_prost(schema_id, database_id);
+    let mut materialize = plan_root.gen_create_mv_plan(table_name)?;
+    if let Some(backfill_order) = context.with_options().get(BACKFILL_ORDER) {
+        let backfill_order =
+            resolve_backfill_order(session, &schema_name, materialize.input(), backfill_order)?;
+        materialize = materialize.clone_with_table(TableCatalog {
+            backfill_order,
+            ..materialize.table().clone()
+        });
+    }
+    let table = materialize.to_table_prost(schema_id, database_id);
     let plan: PlanRef = materialize.into();
 
     Ok((plan, table))
 }
 
+/// Collects the `WITH` options of a `CREATE MATERIALIZED VIEW` into a map. Values can be numbers,
+/// booleans or single quoted strings.
+pub(crate) fn handle_mv_with_options(options: Vec<SqlOption>) -> Result<HashMap<String, String>> {
+    options
+        .into_iter()
+        .map(|option| match option.value {
+            Value::Number(v, _) | Value::SingleQuotedString(v) => {
+                Ok((option.name.value.to_lowercase(), v))
+            }
+            Value::Boolean(v) => Ok((option.name.value.to_lowercase(), v.to_string())),
+            v => Err(InvalidInputSyntax(format!(
+                "unsupported value {} for option {}",
+                v, option.name
+            ))
+            .into()),
+        })
+        .collect()
+}
+
+/// Whether a table, materialized view or source named `name` exists.
+fn relation_exists(session: &SessionImpl, name: ObjectName) -> Result<bool> {
+    let (schema_name, relation_name) = Binder::resolve_table_name(name)?;
+    let catalog = session.env().catalog_reader().read_guard();
+    Ok(catalog
+        .get_table_by_name(session.database(), &schema_name, &relation_name)
+        .is_ok()
+        || catalog
+            .get_source_by_name(session.database(), &schema_name, &relation_name)
+            .is_ok())
+}
+
 pub async fn handle_create_mv(
-    context: OptimizerContext,
+    mut context: OptimizerContext,
     name: ObjectName,
     query: Box<Query>,
+    with_options: Vec<SqlOption>,
+    if_not_exists: bool,
 ) -> Result<PgResponse> {
     let session = context.session_ctx.clone();
+    // Skip planning the query at all if there is nothing to create.
+    if if_not_exists && relation_exists(&session, name.clone())? {
+        let notice = format!("relation \"{}\" already exists, skipping", name);
+        return Ok(
+            PgResponse::empty_result(StatementType::CREATE_MATERIALIZED_VIEW)
+                .with_notices(vec![notice]),
+        );
+    }
+    context.with_options = handle_mv_with_options(with_options)?;
+    let context: OptimizerContextRef = context.into();
 
     let (table, stream_plan) = {
-        let (plan, table) = gen_create_mv_plan(&session, context.into(), query, name)?;
+        let (plan, table) = gen_create_mv_plan(&session, context.clone(), query, name)?;
         let stream_plan = plan.to_stream_prost();
         (table, stream_plan)
     };
+    let notices = context.notices();
 
     let catalog_writer = session.env().catalog_writer();
     catalog_writer
         .create_materialized_view(table, stream_plan)
         .await?;
 
-    Ok(PgResponse::empty_result(
-        StatementType::CREATE_MATERIALIZED_VIEW,
-    ))
+    Ok(PgResponse::empty_result(StatementType::CREATE_MATERIALIZED_VIEW).with_notices(notices))
 }
 
 #[cfg(test)]
@@ -82,10 +287,15 @@ pub mod tests {
     use std::collections::HashMap;
 
     use itertools::Itertools;
-    use risingwave_common::catalog::{DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME};
+    use risingwave_common::catalog::{TableId, DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME};
     use risingwave_common::types::DataType;
+    use risingwave_sqlparser::ast::Statement;
+    use risingwave_sqlparser::parser::Parser;
 
-    use crate::catalog::gen_row_id_column_name;
+    use super::gen_create_mv_plan;
+    use crate::catalog::{gen_row_id_column_name, ColumnId};
+    use crate::optimizer::plan_node::{collect_dependent_table_ids, ColumnLineage};
+    use crate::session::OptimizerContext;
     use crate::test_utils::{create_proto_file, LocalFrontend, PROTO_FILE_DATA};
 
     #[tokio::test]
@@ -149,4 +359,513 @@ pub mod tests {
         };
         assert_eq!(columns, expected_columns);
     }
+
+    #[tokio::test]
+    async fn test_collect_dependent_table_ids() {
+        let proto_file = create_proto_file(PROTO_FILE_DATA);
+        let sql = format!(
+            r#"CREATE SOURCE t1
+    WITH ('kafka.topic' = 'abc', 'kafka.servers' = 'localhost:1001')
+    ROW FORMAT PROTOBUF MESSAGE '.test.TestRecord' ROW SCHEMA LOCATION 'file://{}'"#,
+            proto_file.path().to_str().unwrap()
+        );
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql(sql).await.unwrap();
+        frontend
+            .run_sql("create materialized view m1 as select id from t1")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create materialized view m2 as select id from t1")
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let (query, name) = match Parser::parse_sql(
+            "create materialized view m3 as select m1.id from m1 join m2 on m1.id = m2.id \
+             join t1 on m1.id = t1.id",
+        )
+        .unwrap()
+        .remove(0)
+        {
+            Statement::CreateView { query, name, .. } => (query, name),
+            _ => unreachable!(),
+        };
+        let context = OptimizerContext::new(session.clone()).into();
+        let (plan, _) = gen_create_mv_plan(&session, context, query, name).unwrap();
+
+        let (m1, m2, t1) = {
+            let catalog = session.env().catalog_reader().read_guard();
+            let table_id = |name: &str| {
+                catalog
+                    .get_table_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, name)
+                    .unwrap()
+                    .id()
+            };
+            let source = catalog
+                .get_source_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, "t1")
+                .unwrap();
+            (table_id("m1"), table_id("m2"), TableId::new(source.id))
+        };
+        let mut table_ids = collect_dependent_table_ids(plan.clone(), false);
+        table_ids.sort_by_key(|id| id.table_id);
+        let mut expected = vec![m1, m2, t1];
+        expected.sort_by_key(|id| id.table_id);
+        assert_eq!(table_ids, expected);
+
+        let table_ids = collect_dependent_table_ids(plan, true);
+        assert_eq!(table_ids.len(), 4);
+        assert!(table_ids.contains(&TableId::placeholder()));
+    }
+
+    #[tokio::test]
+    async fn test_column_provenance() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int, v2 int)")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create table t2 (v1 int, v2 int)")
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let lineage = |table: &str, column_id| {
+            let table_id = session
+                .env()
+                .catalog_reader()
+                .read_guard()
+                .get_table_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, table)
+                .unwrap()
+                .id();
+            Some(ColumnLineage {
+                table_id,
+                column_id: ColumnId::new(column_id),
+            })
+        };
+        let provenance = |sql: &str| {
+            let (query, name) = match Parser::parse_sql(sql).unwrap().remove(0) {
+                Statement::CreateView { query, name, .. } => (query, name),
+                _ => unreachable!(),
+            };
+            let context = OptimizerContext::new(session.clone()).into();
+            let (plan, _) = gen_create_mv_plan(&session, context, query, name).unwrap();
+            plan.as_stream_materialize().unwrap().column_provenance()
+        };
+
+        // `v1` and `v2` of a table are its columns 1 and 2, after the row id.
+        let columns = provenance("create materialized view mv as select v2, v1 + 1, v1 from t");
+        assert_eq!(columns[..3], [lineage("t", 2), None, lineage("t", 1)]);
+
+        let columns = provenance(
+            "create materialized view mv as select t.v1, t2.v2 from t join t2 on t.v1 = t2.v1",
+        );
+        assert_eq!(columns[..2], [lineage("t", 1), lineage("t2", 2)]);
+
+        let outer_join = |join: &str| {
+            provenance(&format!(
+                "create materialized view mv as select t.v1, t2.v2 from t {} t2 on t.v1 = t2.v1",
+                join
+            ))
+        };
+        assert_eq!(outer_join("left join")[..2], [lineage("t", 1), None]);
+        assert_eq!(outer_join("right join")[..2], [None, lineage("t2", 2)]);
+        assert_eq!(outer_join("full join")[..2], [None, None]);
+
+        let columns =
+            provenance("create materialized view mv as select v1, count(*) from t group by v1");
+        assert_eq!(columns, vec![lineage("t", 1), None]);
+    }
+
+    #[tokio::test]
+    async fn test_check_self_reference() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int)").await.unwrap();
+        frontend
+            .run_sql("create materialized view mv as select * from t")
+            .await
+            .unwrap();
+
+        let err = frontend
+            .run_sql("create materialized view mv as select * from mv")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid input syntax: materialized view mv cannot reference itself"
+        );
+        let err = frontend
+            .run_sql("create materialized view mv as select t.v1 from t join mv on t.v1 = mv.v1")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid input syntax: materialized view mv cannot reference itself"
+        );
+        let err = frontend
+            .run_sql(
+                "create materialized view mv as select * from t where v1 in (select v1 from mv)",
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid input syntax: materialized view mv cannot reference itself"
+        );
+
+        // Reusing the name of a relation not read is still just a duplicated name.
+        let err = frontend
+            .run_sql("create materialized view mv as select * from t")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("with name mv exists"));
+        frontend
+            .run_sql("create materialized view mv2 as select * from mv")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_create_mv_with_rate_limit() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int)").await.unwrap();
+        frontend
+            .run_sql(
+                "create materialized view mv with (streaming_rate_limit = 100) as select * from t",
+            )
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let table = session
+            .env()
+            .catalog_reader()
+            .read_guard()
+            .get_table_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, "mv")
+            .unwrap()
+            .clone();
+        assert_eq!(table.backfill_rate_limit(), Some(100));
+
+        let explain = frontend
+            .run_sql(
+                "explain create materialized view mv2 with (streaming_rate_limit = 100) as select * from t",
+            )
+            .await
+            .unwrap();
+        let root = explain.iter().next().unwrap().values()[0].clone().unwrap();
+        assert!(root.starts_with("StreamMaterialize"));
+        assert!(root.ends_with(", backfill_rate_limit: 100 }"));
+
+        assert!(frontend
+            .run_sql(
+                "create materialized view mv3 with (streaming_rate_limit = 0) as select * from t"
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_mv_with_prefix_len() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int, v2 int)")
+            .await
+            .unwrap();
+        frontend
+            .run_sql(
+                "create materialized view mv with (prefix_len = 1) as select v1 from t order by v1",
+            )
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let table = session
+            .env()
+            .catalog_reader()
+            .read_guard()
+            .get_table_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, "mv")
+            .unwrap()
+            .clone();
+        assert_eq!(table.pk_desc().len(), 2);
+        assert_eq!(table.read_prefix_len_hint(), Some(1));
+        assert_eq!(table.effective_read_prefix_len(), 1);
+
+        // The stream key of the aggregation is `v1, v2`, so a single column prefix is too short.
+        let err = frontend
+            .run_sql(
+                "create materialized view mv2 with (prefix_len = 1) as \
+                 select v1, v2, count(*) from t group by v1, v2",
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid input syntax: prefix_len of materialized view mv2 must be between the stream \
+             key length 2 and the pk length 2, got 1"
+        );
+
+        assert!(frontend
+            .run_sql(
+                "create materialized view mv3 with (prefix_len = 3) as select v1 from t order by v1"
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_mv_with_vnode_count() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int)").await.unwrap();
+        frontend
+            .run_sql("create materialized view mv with (vnode_count = 64) as select * from t")
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let table = session
+            .env()
+            .catalog_reader()
+            .read_guard()
+            .get_table_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, "mv")
+            .unwrap()
+            .clone();
+        assert_eq!(table.vnode_count(), Some(64));
+
+        for vnode_count in ["0", "24", "4096"] {
+            assert!(frontend
+                .run_sql(format!(
+                    "create materialized view mv2 with (vnode_count = {}) as select * from t",
+                    vnode_count
+                ))
+                .await
+                .is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_mv_with_force_append_only() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int, v2 int)")
+            .await
+            .unwrap();
+        let response = frontend
+            .run_sql(
+                "create materialized view mv with (force_append_only = true) as \
+                 select v1, count(*) from t group by v1",
+            )
+            .await
+            .unwrap();
+        // The aggregation updates its rows, so the user is warned.
+        assert_eq!(response.get_notices().len(), 1);
+        assert!(response.get_notices()[0]
+            .starts_with("materialized view mv is forced to be append-only"));
+        let response = frontend
+            .run_sql("create materialized view mv2 as select v1, count(*) from t group by v1")
+            .await
+            .unwrap();
+        assert!(response.get_notices().is_empty());
+
+        let session = frontend.session_ref();
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let get_table = |name| {
+            catalog_reader
+                .get_table_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, name)
+                .unwrap()
+                .clone()
+        };
+        assert!(get_table("mv").append_only());
+        assert!(!get_table("mv2").append_only());
+        drop(catalog_reader);
+
+        assert!(frontend
+            .run_sql(
+                "create materialized view mv3 with (force_append_only = 'yes') as select * from t"
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_notice_pk_of_all_columns() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int, v2 int)")
+            .await
+            .unwrap();
+
+        // Joining two sets of keys keeps both keys, which together cover all columns.
+        let sql = "create materialized view mv as select a.v1 as a1, b.v1 as b1 \
+                   from (select v1 from t group by v1) a \
+                   join (select v1 from t group by v1) b on a.v1 = b.v1";
+        for response in [
+            frontend.run_sql(format!("explain {}", sql)).await.unwrap(),
+            frontend.run_sql(sql).await.unwrap(),
+        ] {
+            assert_eq!(response.get_notices().len(), 1);
+            assert!(response.get_notices()[0]
+                .starts_with("the pk of materialized view mv covers all of its 2 columns"));
+        }
+
+        for sql in [
+            "create materialized view mv2 as select v1, count(*) from t group by v1",
+            "create materialized view mv3 as select v1, v2 from t group by v1, v2",
+            "create materialized view mv4 as select sum(v1), count(*) from t",
+            "create table t2 ()",
+        ] {
+            let response = frontend.run_sql(sql).await.unwrap();
+            assert!(response.get_notices().is_empty(), "{}", sql);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_mv_with_distribution_prefix() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int, v2 int)")
+            .await
+            .unwrap();
+        let sql = |prefix| {
+            format!(
+                "explain create materialized view mv with (distribution_prefix = {}) as \
+                 select v1, v2, count(*) from t group by v1, v2",
+                prefix
+            )
+        };
+
+        let explain = frontend.run_sql(sql(1)).await.unwrap();
+        let root = explain.iter().next().unwrap().values()[0].clone().unwrap();
+        assert!(root.contains("dist_key: [v1]"));
+        let explain = frontend.run_sql(sql(2)).await.unwrap();
+        let root = explain.iter().next().unwrap().values()[0].clone().unwrap();
+        assert!(root.contains("dist_key: [v1, v2]"));
+
+        let err = frontend.run_sql(sql(0)).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid input syntax: distribution_prefix of materialized view mv must be between 1 \
+             and the stream key length 2, got 0"
+        );
+        assert!(frontend.run_sql(sql(3)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_mv_if_not_exists() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int)").await.unwrap();
+        let response = frontend
+            .run_sql("create materialized view if not exists mv as select * from t")
+            .await
+            .unwrap();
+        assert!(response.get_notices().is_empty());
+        let response = frontend
+            .run_sql("create materialized view if not exists mv as select * from t")
+            .await
+            .unwrap();
+        assert_eq!(
+            response.get_notices(),
+            &["relation \"mv\" already exists, skipping".to_string()]
+        );
+        assert!(frontend
+            .run_sql("create materialized view mv as select * from t")
+            .await
+            .is_err());
+
+        // The query is not even bound when the materialized view exists, so the missing table
+        // goes unnoticed.
+        frontend
+            .run_sql("create materialized view if not exists mv as select * from missing")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create materialized view if not exists t as select * from missing")
+            .await
+            .unwrap();
+        assert!(frontend
+            .run_sql("create materialized view if not exists mv2 as select * from missing")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_explain_arrangement_backfill() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int)").await.unwrap();
+
+        let explain = |verbose: &'static str| {
+            let frontend = &frontend;
+            async move {
+                let sql = format!(
+                    "explain {} create materialized view mv as select * from t",
+                    verbose
+                );
+                let response = frontend.run_sql(sql).await.unwrap();
+                let row = response.iter().next().unwrap();
+                row.values()[0].clone().unwrap()
+            }
+        };
+        assert!(explain("verbose")
+            .await
+            .contains(", arrangement_backfill: true"));
+        assert!(!explain("").await.contains("arrangement_backfill"));
+    }
+
+    #[tokio::test]
+    async fn test_create_mv_with_backfill_order() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table dim (k int)").await.unwrap();
+        frontend
+            .run_sql("create table fact (k int, v int)")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create table other (k int)")
+            .await
+            .unwrap();
+
+        let create_mv = |name: &str, with: &str| {
+            format!(
+                "create materialized view {} {} as select fact.v from fact join dim on fact.k = dim.k",
+                name, with
+            )
+        };
+        frontend
+            .run_sql(create_mv("mv1", "with (backfill_order = 'dim, fact')"))
+            .await
+            .unwrap();
+        frontend.run_sql(create_mv("mv2", "")).await.unwrap();
+        assert!(frontend
+            .run_sql(create_mv("mv3", "with (backfill_order = 'other')"))
+            .await
+            .is_err());
+        for (option, err) in [
+            (
+                "'dim, dim'",
+                "dim is listed more than once in backfill_order",
+            ),
+            ("'dim,'", "empty relation name in backfill_order 'dim,'"),
+            (
+                "' , fact'",
+                "empty relation name in backfill_order ' , fact'",
+            ),
+        ] {
+            let with = format!("with (backfill_order = {})", option);
+            let e = frontend.run_sql(create_mv("mv3", &with)).await.unwrap_err();
+            assert_eq!(e.to_string(), format!("Invalid input syntax: {}", err));
+        }
+
+        let session = frontend.session_ref();
+        let catalog = session.env().catalog_reader().read_guard();
+        let table = |name: &str| {
+            catalog
+                .get_table_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, name)
+                .unwrap()
+        };
+        assert_eq!(
+            table("mv1").backfill_order(),
+            &[table("dim").id(), table("fact").id()]
+        );
+        assert!(table("mv2").backfill_order().is_empty());
+    }
 }
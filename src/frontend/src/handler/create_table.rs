@@ -12,22 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use fixedbitset::FixedBitSet;
 use itertools::Itertools;
 use pgwire::pg_response::{PgResponse, StatementType};
 use risingwave_common::catalog::{ColumnDesc, ColumnId};
+use risingwave_common::error::ErrorCode::InvalidInputSyntax;
 use risingwave_common::error::Result;
 use risingwave_pb::catalog::source::Info;
 use risingwave_pb::catalog::{Source as ProstSource, Table as ProstTable, TableSourceInfo};
 use risingwave_pb::plan::ColumnCatalog;
-use risingwave_sqlparser::ast::{ColumnDef, ObjectName};
+use risingwave_sqlparser::ast::{ColumnDef, ObjectName, SqlOption};
 
+use super::create_mv::handle_mv_with_options;
 use super::create_source::make_prost_source;
 use crate::binder::expr::bind_data_type;
 use crate::catalog::{check_valid_column_name, row_id_column_desc};
-use crate::optimizer::plan_node::{LogicalSource, StreamSource};
+use crate::optimizer::plan_node::{LogicalSource, StreamSource, FORCE_APPEND_ONLY, VNODE_COUNT};
 use crate::optimizer::property::{Distribution, Order};
 use crate::optimizer::{PlanRef, PlanRoot};
 use crate::session::{OptimizerContext, OptimizerContextRef, SessionImpl};
@@ -64,6 +67,24 @@ pub fn bind_sql_columns(columns: Vec<ColumnDef>) -> Result<Vec<ColumnCatalog>> {
     Ok(columns_catalog)
 }
 
+/// Collects the `WITH` options of a `CREATE TABLE` into a map. Only [`VNODE_COUNT`] applies to a
+/// table, the other options of a materialized view are rejected rather than ignored.
+pub(crate) fn handle_table_with_options(
+    options: Vec<SqlOption>,
+) -> Result<HashMap<String, String>> {
+    let options = handle_mv_with_options(options)?;
+    // A table takes updates and deletes, which an append-only table would silently drop.
+    if options.contains_key(FORCE_APPEND_ONLY) {
+        return Err(
+            InvalidInputSyntax(format!("{} is not allowed for table", FORCE_APPEND_ONLY)).into(),
+        );
+    }
+    if let Some(name) = options.keys().find(|name| name.as_str() != VNODE_COUNT) {
+        return Err(InvalidInputSyntax(format!("unsupported option {} for table", name)).into());
+    }
+    Ok(options)
+}
+
 pub(crate) fn gen_create_table_plan(
     session: &SessionImpl,
     context: OptimizerContextRef,
@@ -111,15 +132,18 @@ pub(crate) fn gen_materialized_source_plan(
 }
 
 pub async fn handle_create_table(
-    context: OptimizerContext,
+    mut context: OptimizerContext,
     table_name: ObjectName,
     columns: Vec<ColumnDef>,
+    with_options: Vec<SqlOption>,
 ) -> Result<PgResponse> {
     let session = context.session_ctx.clone();
+    context.with_options = handle_table_with_options(with_options)?;
+    let context: OptimizerContextRef = context.into();
 
     let (plan, source, table) = {
         let (plan, source, table) =
-            gen_create_table_plan(&session, context.into(), table_name.clone(), columns)?;
+            gen_create_table_plan(&session, context.clone(), table_name.clone(), columns)?;
         let plan = plan.to_stream_prost();
 
         (plan, source, table)
@@ -136,7 +160,7 @@ pub async fn handle_create_table(
         .create_materialized_source(source, table, plan)
         .await?;
 
-    Ok(PgResponse::empty_result(StatementType::CREATE_TABLE))
+    Ok(PgResponse::empty_result(StatementType::CREATE_TABLE).with_notices(context.notices()))
 }
 
 #[cfg(test)]
@@ -191,5 +215,70 @@ mod tests {
         };
 
         assert_eq!(columns, expected_columns);
+        assert_eq!(table.vnode_count(), None);
+    }
+
+    #[tokio::test]
+    async fn test_create_table_with_vnode_count() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int) with (vnode_count = 16);")
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let table = session
+            .env()
+            .catalog_reader()
+            .read_guard()
+            .get_table_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, "t")
+            .unwrap()
+            .clone();
+        assert_eq!(table.vnode_count(), Some(16));
+
+        let err = frontend
+            .run_sql("create table t2 (v1 int) with (vnode_count = 12);")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid input syntax: vnode_count must be a power of two no greater than 2048, got 12"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_table_with_force_append_only() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        let err = frontend
+            .run_sql("create table t (v1 int) with (force_append_only = true);")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid input syntax: force_append_only is not allowed for table"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_table_with_unsupported_options() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        for option in [
+            "streaming_rate_limit = 100",
+            "distribution_prefix = 1",
+            "foo = 1",
+        ] {
+            let err = frontend
+                .run_sql(format!("create table t (v1 int) with ({});", option))
+                .await
+                .unwrap_err();
+            let name = option.split(' ').next().unwrap();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "Invalid input syntax: unsupported option {} for table",
+                    name
+                )
+            );
+        }
     }
 }
@@ -18,17 +18,27 @@ use pgwire::types::Row;
 use risingwave_common::error::Result;
 use risingwave_sqlparser::ast::Statement;
 
-use super::create_mv::gen_create_mv_plan;
-use super::create_table::gen_create_table_plan;
+use super::create_mv::{gen_create_mv_plan, handle_mv_with_options};
+use super::create_table::{gen_create_table_plan, handle_table_with_options};
 use crate::binder::Binder;
 use crate::planner::Planner;
 use crate::session::OptimizerContext;
 
 pub(super) fn handle_explain(
-    context: OptimizerContext,
+    mut context: OptimizerContext,
     stmt: Statement,
-    _verbose: bool,
+    verbose: bool,
 ) -> Result<PgResponse> {
+    context.explain_verbose = verbose;
+    match &stmt {
+        Statement::CreateView { with_options, .. } => {
+            context.with_options = handle_mv_with_options(with_options.clone())?;
+        }
+        Statement::CreateTable { with_options, .. } => {
+            context.with_options = handle_table_with_options(with_options.clone())?;
+        }
+        _ => {}
+    }
     let session = context.session_ctx.clone();
     // bind, plan, optimize, and serialize here
     let mut planner = Planner::new(context.into());
@@ -74,5 +84,6 @@ pub(super) fn handle_explain(
             "QUERY PLAN".to_owned(),
             TypeOid::Varchar,
         )],
-    ))
+    )
+    .with_notices(planner.ctx().notices()))
 }
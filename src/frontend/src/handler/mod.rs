@@ -45,9 +45,12 @@ pub(super) async fn handle(session: Arc<SessionImpl>, stmt: Statement) -> Result
             is_materialized,
             stmt,
         } => create_source::handle_create_source(context, is_materialized, stmt).await,
-        Statement::CreateTable { name, columns, .. } => {
-            create_table::handle_create_table(context, name, columns).await
-        }
+        Statement::CreateTable {
+            name,
+            columns,
+            with_options,
+            ..
+        } => create_table::handle_create_table(context, name, columns, with_options).await,
         Statement::Describe { name } => describe::handle_describe(context, name).await,
         // TODO: support complex sql for `show columns from <table>`
         Statement::ShowColumn { name } => describe::handle_describe(context, name).await,
@@ -79,8 +82,10 @@ pub(super) async fn handle(session: Arc<SessionImpl>, stmt: Statement) -> Result
             or_replace: false,
             name,
             query,
+            with_options,
+            if_not_exists,
             ..
-        } => create_mv::handle_create_mv(context, name, query).await,
+        } => create_mv::handle_create_mv(context, name, query, with_options, if_not_exists).await,
         Statement::Flush => flush::handle_flush(context).await,
         Statement::SetVariable {
             local: _,
@@ -28,7 +28,7 @@ use risingwave_common::catalog::Schema;
 use risingwave_common::error::Result;
 
 use self::heuristic::{ApplyOrder, HeuristicOptimizer};
-use self::plan_node::{Convention, LogicalProject, StreamMaterialize};
+use self::plan_node::{Convention, LogicalProject, MaterializeOptions, StreamMaterialize};
 use self::rule::*;
 use crate::expr::InputRef;
 
@@ -183,6 +183,12 @@ impl PlanRoot {
                     .unwrap();
                 self.out_fields = out_col_change.rewrite_bitset(&self.out_fields);
                 self.schema = plan.schema().clone();
+                if let Some(prefix_len) =
+                    StreamMaterialize::resolve_distribution_prefix(&plan, &mv_name)?
+                {
+                    self.required_dist =
+                        Distribution::HashShard(plan.pk_indices()[..prefix_len].to_vec());
+                }
                 plan.to_stream_with_dist_required(&self.required_dist)
             }
             Convention::Stream => self
@@ -197,12 +203,13 @@ impl PlanRoot {
         // Convert to physical plan node, using distribution of the input node
         // After that, we will need to wrap a `MaterializeExecutor` on it in `gen_create_mv_plan`.
 
-        StreamMaterialize::create(
+        Ok(StreamMaterialize::create(
             stream_plan,
             mv_name,
             self.required_order.clone(),
             self.out_fields.clone(),
-        )
+            MaterializeOptions::default(),
+        )?)
     }
 
     /// Set the plan root's required dist.
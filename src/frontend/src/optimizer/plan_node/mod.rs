@@ -271,7 +271,11 @@ pub use stream_exchange::StreamExchange;
 pub use stream_filter::StreamFilter;
 pub use stream_hash_agg::StreamHashAgg;
 pub use stream_hash_join::StreamHashJoin;
-pub use stream_materialize::StreamMaterialize;
+pub use stream_materialize::{
+    collect_dependent_table_ids, ColumnLineage, MaterializeError, MaterializeOptions,
+    MaterializeTableRewriter, StateSizeClass, StreamMaterialize, DISTRIBUTION_PREFIX,
+    FORCE_APPEND_ONLY, VNODE_COUNT,
+};
 pub use stream_project::StreamProject;
 pub use stream_simple_agg::StreamSimpleAgg;
 pub use stream_source::StreamSource;
@@ -60,6 +60,12 @@ impl StreamHashAgg {
     pub fn distribution_keys(&self) -> &[usize] {
         self.logical.group_keys()
     }
+
+    /// Get a reference to the stream hash agg's logical.
+    #[must_use]
+    pub fn logical(&self) -> &LogicalAgg {
+        &self.logical
+    }
 }
 
 impl fmt::Display for StreamHashAgg {
@@ -73,6 +73,12 @@ impl StreamHashJoin {
         &self.eq_join_predicate
     }
 
+    /// Get a reference to the stream hash join's logical.
+    #[must_use]
+    pub fn logical(&self) -> &LogicalJoin {
+        &self.logical
+    }
+
     fn derive_dist(
         left: &Distribution,
         right: &Distribution,
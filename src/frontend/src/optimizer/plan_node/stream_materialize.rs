@@ -12,25 +12,40 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::borrow::Cow;
 use std::collections::HashSet;
 use std::fmt;
+use std::rc::Rc;
 
 use fixedbitset::FixedBitSet;
 use itertools::Itertools;
 use risingwave_common::catalog::{Field, OrderedColumnDesc, Schema, TableId};
-use risingwave_common::error::ErrorCode::InternalError;
-use risingwave_common::error::Result;
+use risingwave_common::error::ErrorCode::{InternalError, InvalidInputSyntax};
+use risingwave_common::error::RwError;
+use risingwave_common::hash::VIRTUAL_NODE_COUNT;
 use risingwave_common::util::sort_util::OrderType;
+use risingwave_pb::catalog::Table as ProstTable;
 use risingwave_pb::expr::InputRefExpr;
-use risingwave_pb::plan::ColumnOrder;
+use risingwave_pb::plan::{ColumnOrder, JoinType};
 use risingwave_pb::stream_plan::stream_node::Node as ProstStreamNode;
+use risingwave_sqlparser::ast::{Ident, Value};
+use thiserror::Error;
 
-use super::{PlanRef, PlanTreeNodeUnary, ToStreamProst};
+use super::{
+    BatchSeqScan, LogicalScan, LogicalSource, PlanRef, PlanTreeNode, PlanTreeNodeBinary,
+    PlanTreeNodeUnary, StreamExchange, StreamHashAgg, StreamHashJoin, StreamSimpleAgg,
+    StreamSource, StreamTableScan, ToStreamProst,
+};
 use crate::catalog::column_catalog::ColumnCatalog;
 use crate::catalog::table_catalog::TableCatalog;
-use crate::catalog::{gen_row_id_column_name, is_row_id_column_name, ColumnId};
+use crate::catalog::{
+    gen_row_id_column_name, is_row_id_column_name, ColumnId, DatabaseId, SchemaId,
+};
+use crate::expr::ExprImpl;
 use crate::optimizer::plan_node::{PlanBase, PlanNode};
-use crate::optimizer::property::Order;
+use crate::optimizer::plan_rewriter::PlanRewriter;
+use crate::optimizer::plan_visitor::PlanVisitor;
+use crate::optimizer::property::{Distribution, FieldOrder, Order};
 
 /// Materializes a stream.
 #[derive(Debug, Clone)]
@@ -38,39 +53,201 @@ pub struct StreamMaterialize {
     pub base: PlanBase,
     /// Child of Materialize plan
     input: PlanRef,
-    table: TableCatalog,
+    /// Shared among the clones made while the plan is rewritten, as the catalog never changes
+    /// with the input.
+    table: Rc<TableCatalog>,
+}
+
+/// The `WITH` option that limits the rate of backfilling a materialized view, in rows per second.
+pub const STREAMING_RATE_LIMIT: &str = "streaming_rate_limit";
+
+/// The `WITH` option that narrows the pk prefix reads of a materialized view scan by. It must
+/// keep the stream key scannable, so it is at least the length of the stream key.
+pub const PREFIX_LEN: &str = "prefix_len";
+
+/// The `WITH` option that sets the number of vnodes a materialized view is initially distributed
+/// over. It must be a power of two no greater than [`VIRTUAL_NODE_COUNT`].
+pub const VNODE_COUNT: &str = "vnode_count";
+
+/// The `WITH` option that distributes a materialized view by a prefix of its stream key rather
+/// than the distribution it inherits from its input, so that rows sharing the prefix are
+/// colocated.
+pub const DISTRIBUTION_PREFIX: &str = "distribution_prefix";
+
+/// The `WITH` option that marks a materialized view append-only even if its input is not known to
+/// be. The user takes the responsibility that the input only inserts.
+pub const FORCE_APPEND_ONLY: &str = "force_append_only";
+
+/// Table ids from this one up are reserved: meta allocates table ids as non-negative `i32`s, so
+/// it never hands them out. [`TableId::placeholder`] is among them.
+const RESERVED_TABLE_ID_START: u32 = i32::MAX as u32 + 1;
+
+/// The reasons creating a [`StreamMaterialize`] can fail for.
+#[derive(Error, Debug, PartialEq)]
+pub enum MaterializeError {
+    #[error("cannot reuse the placeholder table id {0} for materialized view {1}")]
+    PlaceholderTableId(TableId, String),
+    #[error("cannot reuse the reserved table id {0} for materialized view {1}")]
+    ReservedTableId(TableId, String),
+    #[error("materialized view {0} has no stream key to derive its pk from")]
+    EmptyPk(String),
+    #[error("materialized view {0} cannot be distributed by an empty key")]
+    EmptyDistKey(String),
+    #[error("materialized view {0} cannot be created over a broadcast input")]
+    BroadcastInput(String),
+    #[error("distribution key {0} of materialized view {1} is out of range of its {2} columns")]
+    DistKeyOutOfRange(usize, String, usize),
+    #[error("invalid mirror distribution key {0:?} for materialized view {1}")]
+    InvalidMirrorDistKey(Vec<usize>, String),
+    #[error("mirror of materialized view {0} must be distributed differently from it")]
+    MirrorSameDist(String),
+    #[error("mirror of materialized view {0} must be named differently from it")]
+    MirrorSameName(String),
+    #[error("column {0} specified more than once in materialized view {1}")]
+    DuplicateColumnName(String, String),
+    #[error("{} must be a positive integer, got {0}", STREAMING_RATE_LIMIT)]
+    InvalidRateLimit(String),
+    #[error(
+        "{} of materialized view {0} must be between the stream key length {1} and the pk length {2}, got {3}",
+        PREFIX_LEN
+    )]
+    InvalidPrefixLen(String, usize, usize, String),
+    #[error(
+        "{} must be a power of two no greater than {}, got {0}",
+        VNODE_COUNT,
+        VIRTUAL_NODE_COUNT
+    )]
+    InvalidVnodeCount(String),
+    #[error(
+        "{} of materialized view {0} must be between 1 and the stream key length {1}, got {2}",
+        DISTRIBUTION_PREFIX
+    )]
+    InvalidDistributionPrefix(String, usize, String),
+    #[error("{} must be true or false, got {0}", FORCE_APPEND_ONLY)]
+    InvalidForceAppendOnly(String),
+    #[error("columns of table {0} do not match the schema {1:?} of its input")]
+    ColumnMismatch(String, Vec<String>),
+    #[error("stream key column {0} is not in the pk of table {1}")]
+    StreamKeyNotInPk(String, String),
+}
+
+impl From<MaterializeError> for RwError {
+    fn from(e: MaterializeError) -> Self {
+        match e {
+            MaterializeError::EmptyPk(..)
+            | MaterializeError::EmptyDistKey(..)
+            | MaterializeError::DuplicateColumnName(..)
+            | MaterializeError::InvalidRateLimit(..)
+            | MaterializeError::InvalidPrefixLen(..)
+            | MaterializeError::InvalidVnodeCount(..)
+            | MaterializeError::InvalidDistributionPrefix(..)
+            | MaterializeError::InvalidForceAppendOnly(..) => {
+                InvalidInputSyntax(e.to_string()).into()
+            }
+            _ => InternalError(e.to_string()).into(),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, MaterializeError>;
+
+/// The optional inputs of [`StreamMaterialize::create`].
+#[derive(Debug, Clone, Default)]
+pub struct MaterializeOptions {
+    /// The id the table catalog takes over instead of a placeholder to be assigned by meta, so
+    /// that a recreated materialized view keeps the id its downstream subscribes to.
+    pub reuse_table_id: Option<TableId>,
+    /// Recorded in the table catalog as is.
+    pub description: Option<String>,
+    /// Orders the rows tied on the user order before falling back to the stream key, so that
+    /// e.g. an `ORDER BY ... LIMIT` over the table returns the same rows across recreations.
+    pub tie_break_by: Option<Order>,
+}
+
+/// The base table column a column of a materialized view passes through unchanged, see
+/// [`StreamMaterialize::column_provenance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnLineage {
+    pub table_id: TableId,
+    pub column_id: ColumnId,
+}
+
+/// A rough class of how much state a materialized view keeps, see
+/// [`StreamMaterialize::estimate_state_class`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateSizeClass {
+    Small,
+    Medium,
+    Large,
 }
 
 impl StreamMaterialize {
-    fn derive_plan_base(input: &PlanRef) -> Result<PlanBase> {
+    fn derive_plan_base(input: &PlanRef) -> PlanBase {
         let ctx = input.ctx();
 
-        let schema = Self::derive_schema(input.schema())?;
+        let schema = Self::derive_schema(input.schema());
         let pk_indices = input.pk_indices();
 
-        Ok(PlanBase::new_stream(
+        PlanBase::new_stream(
             ctx,
             schema,
             pk_indices.to_vec(),
             input.distribution().clone(),
             input.append_only(),
-        ))
+        )
+    }
+
+    /// Resolve the length of the stream key prefix of `input` to distribute the materialized view
+    /// by, from the `distribution_prefix` option of the statement.
+    pub fn resolve_distribution_prefix(input: &PlanRef, mv_name: &str) -> Result<Option<usize>> {
+        let prefix = match input.ctx().with_options().get(DISTRIBUTION_PREFIX) {
+            Some(prefix) => prefix,
+            None => return Ok(None),
+        };
+        let stream_key_len = input.pk_indices().len();
+        match prefix.parse::<usize>() {
+            Ok(len) if len >= 1 && len <= stream_key_len => Ok(Some(len)),
+            _ => Err(MaterializeError::InvalidDistributionPrefix(
+                mv_name.to_string(),
+                stream_key_len,
+                prefix.clone(),
+            )),
+        }
+    }
+
+    /// Whether `name` is generated by the planner rather than given by the user, i.e. a row id
+    /// (`_row_id#N`), an unnamed aggregation (`agg#N`) or an unnamed expression (`expr#N`).
+    fn is_generated_column_name(name: &str) -> bool {
+        if is_row_id_column_name(name) {
+            return true;
+        }
+        ["agg#", "expr#"].iter().any(|prefix| {
+            name.strip_prefix(prefix).map_or(false, |id| {
+                !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+            })
+        })
     }
 
-    fn derive_schema(schema: &Schema) -> Result<Schema> {
+    /// Check that the columns have distinct names. Hidden columns count as well, as the binder
+    /// resolves a column of the materialized view by name whether it is hidden or not. Only
+    /// generated names are allowed to repeat, as they are renamed or never referred to by name.
+    fn check_column_names(schema: &Schema, mv_name: &str) -> Result<()> {
         let mut col_names = HashSet::new();
         for field in schema.fields() {
-            if is_row_id_column_name(&field.name) {
+            if Self::is_generated_column_name(&field.name) {
                 continue;
             }
-            if !col_names.insert(field.name.clone()) {
-                return Err(InternalError(format!(
-                    "column {} specified more than once",
-                    field.name
-                ))
-                .into());
+            if !col_names.insert(&field.name) {
+                return Err(MaterializeError::DuplicateColumnName(
+                    field.name.clone(),
+                    mv_name.to_string(),
+                ));
             }
         }
+        Ok(())
+    }
+
+    fn derive_schema(schema: &Schema) -> Schema {
         let mut row_id_count = 0;
         let fields = schema
             .fields()
@@ -89,27 +266,168 @@ impl StreamMaterialize {
                 false => field.clone(),
             })
             .collect();
-        Ok(Schema { fields })
+        Schema { fields }
     }
 
     #[must_use]
-    pub fn new(input: PlanRef, table: TableCatalog) -> Self {
-        let base = Self::derive_plan_base(&input).unwrap();
+    pub fn new(input: PlanRef, table: Rc<TableCatalog>) -> Self {
+        let mut base = Self::derive_plan_base(&input);
+        base.append_only |= table.append_only();
         Self { base, input, table }
     }
 
+    /// The order the table stores its rows in, i.e. its pk, as column indices of the output. The
+    /// order stops before the first pk column missing from the columns of the table, as the rows
+    /// are only known to be ordered by the pk columns ahead of it.
+    fn derive_order(table: &TableCatalog) -> Order {
+        let field_order = table
+            .pk_desc()
+            .iter()
+            .map_while(|pk| {
+                let index = table
+                    .columns()
+                    .iter()
+                    .position(|c| c.column_id() == pk.column_desc.column_id)?;
+                Some(FieldOrder {
+                    index,
+                    direct: pk.order.into(),
+                })
+            })
+            .collect();
+        Order::new(field_order)
+    }
+
+    /// Rebuild a materialize from a persisted table catalog, e.g. on recovery, without re-running
+    /// [`Self::create`]. Unlike [`Self::new`], the catalog is checked against the input: its
+    /// columns must match the materialized schema, and the stream key of the input must be in its
+    /// pk. The catalog does not record a distribution, so that of the input is used as is.
+    pub fn from_catalog(input: PlanRef, table: TableCatalog) -> Result<Self> {
+        let mut base = Self::derive_plan_base(&input);
+        base.append_only |= table.append_only();
+        let schema = &base.schema;
+        if table.columns().len() != schema.len()
+            || table
+                .columns()
+                .iter()
+                .zip(schema.fields())
+                .any(|(column, field)| {
+                    column.name() != field.name || column.data_type() != &field.data_type
+                })
+        {
+            return Err(MaterializeError::ColumnMismatch(
+                table.name().to_string(),
+                schema.fields().iter().map(|f| f.name.clone()).collect(),
+            ));
+        }
+        let pk_column_ids: HashSet<_> = table
+            .pk_desc()
+            .iter()
+            .map(|c| c.column_desc.column_id)
+            .collect();
+        if let Some(column) = base
+            .pk_indices
+            .iter()
+            .map(|idx| &table.columns()[*idx])
+            .find(|column| !pk_column_ids.contains(&column.column_id()))
+        {
+            return Err(MaterializeError::StreamKeyNotInPk(
+                column.name().to_string(),
+                table.name().to_string(),
+            ));
+        }
+        Ok(Self {
+            base,
+            input,
+            table: Rc::new(table),
+        })
+    }
+
     /// Create a materialize node.
+    ///
+    /// The pk of the table starts with `user_order_by`, followed by `options.tie_break_by` and
+    /// then the stream key columns not in either of them, see [`MaterializeOptions`] for the
+    /// other optional inputs. The backfill rate limit, the read prefix length and the vnode count
+    /// given by the `streaming_rate_limit`, `prefix_len` and `vnode_count` options of the
+    /// statement are recorded in the table catalog. The `force_append_only` option marks the
+    /// materialize append-only regardless of its input.
     pub fn create(
         input: PlanRef,
         mv_name: String,
         user_order_by: Order,
         user_cols: FixedBitSet,
+        options: MaterializeOptions,
     ) -> Result<Self> {
-        let base = Self::derive_plan_base(&input)?;
+        let MaterializeOptions {
+            reuse_table_id,
+            description,
+            tie_break_by,
+        } = options;
+        match reuse_table_id {
+            Some(id) if id == TableId::placeholder() => {
+                return Err(MaterializeError::PlaceholderTableId(id, mv_name));
+            }
+            Some(id) if id.table_id >= RESERVED_TABLE_ID_START => {
+                return Err(MaterializeError::ReservedTableId(id, mv_name));
+            }
+            _ => {}
+        }
+        match input.distribution() {
+            Distribution::HashShard(keys) if keys.is_empty() => {
+                return Err(MaterializeError::EmptyDistKey(mv_name));
+            }
+            // Every parallel materialize would store all rows of a broadcast input.
+            Distribution::Broadcast => {
+                return Err(MaterializeError::BroadcastInput(mv_name));
+            }
+            _ => {}
+        }
+        let backfill_rate_limit = match input.ctx().with_options().get(STREAMING_RATE_LIMIT) {
+            Some(limit) => match limit.parse::<u32>() {
+                Ok(limit) if limit > 0 => Some(limit),
+                _ => return Err(MaterializeError::InvalidRateLimit(limit.clone())),
+            },
+            None => None,
+        };
+        let vnode_count = match input.ctx().with_options().get(VNODE_COUNT) {
+            Some(count) => match count.parse::<u32>() {
+                Ok(n) if n.is_power_of_two() && n as usize <= VIRTUAL_NODE_COUNT => Some(n),
+                _ => return Err(MaterializeError::InvalidVnodeCount(count.clone())),
+            },
+            None => None,
+        };
+        let force_append_only = match input.ctx().with_options().get(FORCE_APPEND_ONLY) {
+            Some(force) => match force.parse::<bool>() {
+                Ok(force) => force,
+                Err(_) => return Err(MaterializeError::InvalidForceAppendOnly(force.clone())),
+            },
+            None => false,
+        };
+        Self::check_column_names(input.schema(), &mv_name)?;
+        let mut base = Self::derive_plan_base(&input);
+        if force_append_only && !base.append_only {
+            base.ctx.warn_to_user(format!(
+                "materialized view {} is forced to be append-only, but its input may update or \
+                 delete rows, which will corrupt it",
+                mv_name
+            ));
+            base.append_only = true;
+        }
         let schema = &base.schema;
         let pk_indices = &base.pk_indices;
+        if let Some(idx) = base
+            .dist
+            .dist_column_indices()
+            .iter()
+            .find(|idx| **idx >= schema.len())
+        {
+            return Err(MaterializeError::DistKeyOutOfRange(
+                *idx,
+                mv_name,
+                schema.len(),
+            ));
+        }
         // Materialize executor won't change the append-only behavior of the stream, so it depends
-        // on input's `append_only`.
+        // on input's `append_only`, unless the user forces it.
         let mut columns = schema
             .fields()
             .iter()
@@ -124,36 +442,173 @@ impl StreamMaterialize {
         // so rewrite ColumnId for each `column_desc` and `column_desc.field_desc`.
         ColumnCatalog::generate_increment_id(&mut columns);
 
-        let mut in_pk = FixedBitSet::with_capacity(schema.len());
-        let mut pk_desc = vec![];
-        for field in &user_order_by.field_order {
-            let idx = field.index;
-            pk_desc.push(OrderedColumnDesc {
-                column_desc: columns[idx].column_desc.clone(),
-                order: field.direct.into(),
-            });
-            in_pk.insert(idx);
+        let pk_desc = Self::derive_pk(&columns, pk_indices, &user_order_by, tie_break_by.as_ref());
+        if pk_desc.is_empty() {
+            return Err(MaterializeError::EmptyPk(mv_name));
+        }
+        let read_prefix_len_hint = match input.ctx().with_options().get(PREFIX_LEN) {
+            Some(len) => {
+                let stream_key_len = pk_indices.iter().unique().count();
+                match len.parse::<u32>() {
+                    Ok(len)
+                        if len > 0
+                            && len as usize >= stream_key_len
+                            && len as usize <= pk_desc.len() =>
+                    {
+                        Some(len)
+                    }
+                    _ => {
+                        return Err(MaterializeError::InvalidPrefixLen(
+                            mv_name,
+                            stream_key_len,
+                            pk_desc.len(),
+                            len.clone(),
+                        ))
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let table = TableCatalog {
+            id: reuse_table_id.unwrap_or_else(TableId::placeholder),
+            associated_source_id: None,
+            name: mv_name,
+            columns,
+            pk_desc,
+            is_mirror: false,
+            vnode_count,
+            description,
+            backfill_rate_limit,
+            backfill_order: vec![],
+            read_prefix_len_hint,
+            append_only: base.append_only,
+        };
+        if cfg!(debug_assertions) {
+            table.assert_stream_key_subset_of_pk(pk_indices);
+        }
+        // The stream key of a single row, or of the group key of an aggregation, covers all
+        // columns by design, as does the row id of a table without columns.
+        if user_order_by.field_order.is_empty()
+            && table.pk_desc.len() == table.columns.len()
+            && table.columns.iter().any(|c| !c.is_hidden)
+            && input.distribution() != &Distribution::Single
+            && !Self::is_keyed_by_group_key(&input)
+        {
+            base.ctx.warn_to_user(format!(
+                "the pk of materialized view {} covers all of its {} columns, which makes its keys \
+                 large; consider adding an explicit ORDER BY or check for a missing GROUP BY",
+                table.name,
+                table.columns.len()
+            ));
+        }
+
+        Ok(Self {
+            base,
+            input,
+            table: Rc::new(table),
+        })
+    }
+
+    /// Whether the stream key of `input` is the group key of an aggregation below it, passed up
+    /// through exchanges, filters and projections.
+    fn is_keyed_by_group_key(input: &PlanRef) -> bool {
+        if input.as_stream_hash_agg().is_some() || input.as_stream_simple_agg().is_some() {
+            return true;
         }
-        for idx in pk_indices.clone() {
+        if input.as_stream_exchange().is_some()
+            || input.as_stream_filter().is_some()
+            || input.as_stream_project().is_some()
+        {
+            return Self::is_keyed_by_group_key(&input.inputs()[0]);
+        }
+        false
+    }
+
+    /// Derives the pk of the table: `user_order_by`, then `tie_break_by`, then the stream key
+    /// columns not in either of them. A column is only taken the first time it appears.
+    fn derive_pk(
+        columns: &[ColumnCatalog],
+        pk_indices: &[usize],
+        user_order_by: &Order,
+        tie_break_by: Option<&Order>,
+    ) -> Vec<OrderedColumnDesc> {
+        let mut in_pk = FixedBitSet::with_capacity(columns.len());
+        let mut pk_desc = vec![];
+        let ordered = user_order_by
+            .field_order
+            .iter()
+            .chain(
+                tie_break_by
+                    .into_iter()
+                    .flat_map(|order| &order.field_order),
+            )
+            .map(|field| (field.index, OrderType::from(field.direct)));
+        // The stream key columns not ordered by the user are appended to make the pk unique. Append
+        // them in ascending column order so that the pk does not depend on how the input happens
+        // to order its stream key.
+        let stream_key = pk_indices
+            .iter()
+            .copied()
+            .sorted()
+            .map(|idx| (idx, OrderType::Ascending));
+        for (idx, order) in ordered.chain(stream_key) {
             if in_pk.contains(idx) {
                 continue;
             }
             pk_desc.push(OrderedColumnDesc {
                 column_desc: columns[idx].column_desc.clone(),
-                order: OrderType::Ascending,
+                order,
             });
             in_pk.insert(idx);
         }
+        pk_desc
+    }
 
-        let table = TableCatalog {
+    /// Create a materialize node together with a mirror of it. Both share `input`, but the mirror
+    /// is resharded by `mirror_dist_key`, so that batch reads can pick the better-distributed
+    /// replica. The mirror is registered as `mirror_name`, which the caller checks against the
+    /// catalog as it does `mv_name`, and stores the same table as the one `options` build.
+    pub fn create_with_mirror(
+        input: PlanRef,
+        mv_name: String,
+        mirror_name: String,
+        user_order_by: Order,
+        user_cols: FixedBitSet,
+        mirror_dist_key: Vec<usize>,
+        options: MaterializeOptions,
+    ) -> Result<(Self, Self)> {
+        if mirror_name == mv_name {
+            return Err(MaterializeError::MirrorSameName(mv_name));
+        }
+        if mirror_dist_key.is_empty()
+            || mirror_dist_key
+                .iter()
+                .any(|idx| *idx >= input.schema().len())
+        {
+            return Err(MaterializeError::InvalidMirrorDistKey(
+                mirror_dist_key,
+                mv_name,
+            ));
+        }
+        let mirror_dist = Distribution::HashShard(mirror_dist_key);
+        if input.distribution() == &mirror_dist {
+            return Err(MaterializeError::MirrorSameDist(mv_name));
+        }
+        let mirror_input = StreamExchange::new(input.clone(), mirror_dist).into();
+
+        let primary = Self::create(input, mv_name, user_order_by, user_cols, options)?;
+        // The mirror stores the same table as the primary, so it is not created again, which
+        // would also repeat the notices of the statement. A reused table id stays with the
+        // primary.
+        let mirror_table = TableCatalog {
             id: TableId::placeholder(),
-            associated_source_id: None,
-            name: mv_name,
-            columns,
-            pk_desc,
+            name: mirror_name,
+            is_mirror: true,
+            ..primary.table().clone()
         };
-
-        Ok(Self { base, input, table })
+        let mirror = Self::new(mirror_input, Rc::new(mirror_table));
+        Ok((primary, mirror))
     }
 
     /// Get a reference to the stream materialize's table.
@@ -166,6 +621,168 @@ impl StreamMaterialize {
         self.table.name()
     }
 
+    /// The order of the rows stored in the table, which follows its pk. Only the table is sorted,
+    /// the stream out of the materialize is not, so this is not the order of the plan node. Batch
+    /// scans over the materialized view do not take it into account yet.
+    pub fn output_order(&self) -> Order {
+        Self::derive_order(&self.table)
+    }
+
+    /// Serialize the table of the materialize as it is registered to the catalog.
+    pub fn to_table_prost(&self, schema_id: SchemaId, database_id: DatabaseId) -> ProstTable {
+        self.table().to_prost(schema_id, database_id)
+    }
+
+    /// Get a reference to the columns of the stream materialize's table.
+    pub fn columns(&self) -> &[ColumnCatalog] {
+        self.table.columns()
+    }
+
+    /// The lineage of each column: the base table column it passes through unchanged, or `None`
+    /// if it is computed, e.g. by an expression or an aggregation, padded with NULLs by an outer
+    /// join, or comes from a source.
+    pub fn column_provenance(&self) -> Vec<Option<ColumnLineage>> {
+        (0..self.schema().len())
+            .map(|idx| Self::trace_column(&self.input, idx))
+            .collect()
+    }
+
+    /// Trace the column `idx` of `plan` down to the base table column it passes through from.
+    fn trace_column(plan: &PlanRef, idx: usize) -> Option<ColumnLineage> {
+        if let Some(scan) = plan.as_stream_table_scan() {
+            let logical = scan.logical();
+            return Some(ColumnLineage {
+                table_id: logical.table_desc().table_id,
+                column_id: logical.column_descs()[idx].column_id,
+            });
+        }
+        if let Some(project) = plan.as_stream_project() {
+            return match &project.logical().exprs()[idx] {
+                ExprImpl::InputRef(input_ref) => {
+                    Self::trace_column(&project.input(), input_ref.index())
+                }
+                _ => None,
+            };
+        }
+        if let Some(join) = plan.as_stream_hash_join() {
+            let logical = join.logical();
+            // The columns of the side an outer join pads with NULLs are derived, not passed
+            // through.
+            let (left_nullable, right_nullable) = match logical.join_type() {
+                JoinType::LeftOuter => (false, true),
+                JoinType::RightOuter => (true, false),
+                JoinType::FullOuter => (true, true),
+                _ => (false, false),
+            };
+            return match (
+                logical.o2l_col_mapping().try_map(idx),
+                logical.o2r_col_mapping().try_map(idx),
+            ) {
+                (Some(left_idx), _) if !left_nullable => Self::trace_column(&join.left(), left_idx),
+                (None, Some(right_idx)) if !right_nullable => {
+                    Self::trace_column(&join.right(), right_idx)
+                }
+                _ => None,
+            };
+        }
+        if let Some(agg) = plan.as_stream_hash_agg() {
+            // Only the group keys pass through an aggregation.
+            return agg
+                .logical()
+                .o2i_col_mapping()
+                .try_map(idx)
+                .and_then(|input_idx| Self::trace_column(&agg.input(), input_idx));
+        }
+        if plan.as_stream_exchange().is_some() || plan.as_stream_filter().is_some() {
+            return Self::trace_column(&plan.inputs()[0], idx);
+        }
+        None
+    }
+
+    /// Estimate the class of the state kept for the materialized view from the shape of its plan,
+    /// before any data flows in:
+    /// - `Large` if any operator below keeps state per key of its input, i.e. a hash aggregation or
+    ///   a hash join, as that state grows with the full history on top of the rows the view itself
+    ///   stores.
+    /// - `Small` if there is none but there is a simple aggregation, so the view holds a single
+    ///   row. Simple aggregations count as constant size, though `min` and `max` over an updatable
+    ///   input actually keep all values.
+    /// - `Medium` otherwise, where only the view stores rows, as many as its input produces.
+    pub fn estimate_state_class(&self) -> StateSizeClass {
+        let mut counter = StatefulOperatorCounter::default();
+        counter.visit(self.input());
+        if counter.keyed_state_count > 0 {
+            StateSizeClass::Large
+        } else if counter.simple_agg_count > 0 {
+            StateSizeClass::Small
+        } else {
+            StateSizeClass::Medium
+        }
+    }
+
+    /// Score in `[0, 1]` of how well the distribution key of the materialized view aligns with
+    /// the partition key of an external system, e.g. the partitioning of a Kafka topic we sink
+    /// into. It is the Jaccard similarity of the two column sets, so `1.0` means rows sharing a
+    /// vnode also share an external partition, and `0.0` means the keys are disjoint. A
+    /// materialize without a hash distribution never aligns.
+    pub fn partition_alignment(&self, external_partition_key: &[usize]) -> f64 {
+        let dist_key: HashSet<usize> = self
+            .base
+            .dist
+            .dist_column_indices()
+            .iter()
+            .copied()
+            .collect();
+        let external_key: HashSet<usize> = external_partition_key.iter().copied().collect();
+        let union = dist_key.union(&external_key).count();
+        if dist_key.is_empty() || union == 0 {
+            return 0.0;
+        }
+        dist_key.intersection(&external_key).count() as f64 / union as f64
+    }
+
+    /// Whether arrangement backfill can read a consistent snapshot of the materialized view. This
+    /// needs the distribution key to be a prefix of the pk, in the same order. A materialize on a
+    /// single node, or sharded by its upstream table rather than by a key, always qualifies.
+    pub fn supports_arrangement_backfill(&self) -> bool {
+        match &self.base.dist {
+            Distribution::Single | Distribution::AnyShard => true,
+            Distribution::HashShard(dist_key) => {
+                let pk_desc = self.table().pk_desc();
+                dist_key.len() <= pk_desc.len()
+                    && dist_key
+                        .iter()
+                        .zip_eq(&pk_desc[..dist_key.len()])
+                        .all(|(idx, pk)| {
+                            self.table().columns()[*idx].column_id() == pk.column_desc.column_id
+                        })
+            }
+            _ => false,
+        }
+    }
+
+    /// Rebuild the materialize with a new table catalog, e.g. to point it at a renamed table. The
+    /// new catalog must describe the same columns as the old one.
+    #[must_use]
+    pub fn clone_with_table(&self, table: TableCatalog) -> Self {
+        assert_eq!(
+            table
+                .columns()
+                .iter()
+                .map(|c| &c.column_desc.data_type)
+                .collect_vec(),
+            self.table()
+                .columns()
+                .iter()
+                .map(|c| &c.column_desc.data_type)
+                .collect_vec()
+        );
+        let new = Self::new(self.input(), Rc::new(table));
+        assert_eq!(new.plan_base().schema, self.plan_base().schema);
+        assert_eq!(new.plan_base().pk_indices, self.plan_base().pk_indices);
+        new
+    }
+
     /// XXX(st1page): this function is used for potential DDL demand in future, and please try your
     /// best not convert `ColumnId` to `usize(col_index`)
     fn col_id_to_idx(&self, id: ColumnId) -> usize {
@@ -173,27 +790,196 @@ impl StreamMaterialize {
     }
 }
 
+/// Quote a column name for explain output unless it is a plain identifier of `[a-z0-9_]`,
+/// doubling any quotes in it as SQL does. Generated names like `_row_id#0` or `agg#0` are quoted
+/// too. Control characters are escaped, so that a name is always printed on one line.
+fn quote_column_name(name: &str) -> Cow<'_, str> {
+    let mut chars = name.chars();
+    let is_plain = chars
+        .next()
+        .map_or(false, |c| c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if is_plain {
+        return Cow::Borrowed(name);
+    }
+    let escaped: String = name
+        .chars()
+        .map(|c| match c {
+            '"' => "\"\"".to_string(),
+            c if c.is_control() => c.escape_default().to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    Cow::Owned(Ident::with_quote('"', escaped).to_string())
+}
+
 impl fmt::Display for StreamMaterialize {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Only the column list marks hidden columns. The pk and distribution key refer back to it
+        // by plain names.
         let column_names = self
-            .table()
             .columns()
             .iter()
-            .map(|c| c.name_with_hidden())
+            .map(|c| {
+                let name = quote_column_name(c.name());
+                if c.is_hidden {
+                    Cow::Owned(format!("{}(hidden)", name))
+                } else {
+                    name
+                }
+            })
             .join(", ");
 
         let pk_column_names = self
             .table()
             .pk_desc()
             .iter()
-            .map(|c| &c.column_desc.name)
+            .map(|c| quote_column_name(&c.column_desc.name))
             .join(", ");
 
         write!(
             f,
-            "StreamMaterialize {{ columns: [{}], pk_columns: [{}] }}",
+            "StreamMaterialize {{ columns: [{}], pk_columns: [{}]",
             column_names, pk_column_names
-        )
+        )?;
+        if let Distribution::HashShard(dist_key) = &self.base.dist && !dist_key.is_empty() {
+            let dist_column_names = dist_key
+                .iter()
+                .map(|idx| quote_column_name(self.columns()[*idx].name()))
+                .join(", ");
+            write!(f, ", dist_key: [{}]", dist_column_names)?;
+        }
+        if let Some(limit) = self.table().backfill_rate_limit() {
+            write!(f, ", backfill_rate_limit: {}", limit)?;
+        }
+        if let Some(len) = self.table().read_prefix_len_hint() {
+            write!(f, ", read_prefix_len: {}", len)?;
+        }
+        if !self.table().backfill_order().is_empty() {
+            write!(
+                f,
+                ", backfill_order: [{}]",
+                self.table()
+                    .backfill_order()
+                    .iter()
+                    .map(|id| id.table_id)
+                    .join(", ")
+            )?;
+        }
+        if let Some(description) = self.table().description() {
+            let literal = Value::SingleQuotedString(description.to_string());
+            write!(f, ", description: {}", literal)?;
+        }
+        if self.table().is_mirror() {
+            write!(f, ", mirror: true")?;
+        }
+        if self.ctx().is_explain_verbose() {
+            write!(
+                f,
+                ", arrangement_backfill: {}",
+                self.supports_arrangement_backfill()
+            )?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// Rewrites the table of every [`StreamMaterialize`] in a plan with the given function.
+pub struct MaterializeTableRewriter<F: FnMut(&TableCatalog) -> TableCatalog> {
+    rewrite_table: F,
+}
+
+impl<F: FnMut(&TableCatalog) -> TableCatalog> MaterializeTableRewriter<F> {
+    pub fn new(rewrite_table: F) -> Self {
+        Self { rewrite_table }
+    }
+}
+
+impl<F: FnMut(&TableCatalog) -> TableCatalog> PlanRewriter for MaterializeTableRewriter<F> {
+    fn rewrite_stream_materialize(&mut self, plan: &StreamMaterialize) -> PlanRef {
+        let input = self.rewrite(plan.input());
+        let plan = plan.clone_with_input(input);
+        let table = (self.rewrite_table)(plan.table());
+        plan.clone_with_table(table).into()
+    }
+}
+
+/// Collect the ids of the tables and sources a plan reads from, deduplicated, in the order they
+/// are first met. The tables of [`StreamMaterialize`] nodes are included only if
+/// `include_materialize` is set.
+pub fn collect_dependent_table_ids(plan: PlanRef, include_materialize: bool) -> Vec<TableId> {
+    let mut collector = DependentTableCollector {
+        include_materialize,
+        table_ids: vec![],
+    };
+    collector.visit(plan);
+    collector.table_ids
+}
+
+struct DependentTableCollector {
+    include_materialize: bool,
+    table_ids: Vec<TableId>,
+}
+
+impl DependentTableCollector {
+    fn add(&mut self, table_id: TableId) {
+        if !self.table_ids.contains(&table_id) {
+            self.table_ids.push(table_id);
+        }
+    }
+}
+
+impl PlanVisitor<()> for DependentTableCollector {
+    fn visit_stream_materialize(&mut self, plan: &StreamMaterialize) {
+        if self.include_materialize {
+            self.add(plan.table().id());
+        }
+        self.visit(plan.input());
+    }
+
+    fn visit_stream_table_scan(&mut self, plan: &StreamTableScan) {
+        self.add(plan.logical().table_desc().table_id);
+    }
+
+    fn visit_stream_source(&mut self, plan: &StreamSource) {
+        self.add(TableId::new(plan.logical().source_catalog.id));
+    }
+
+    fn visit_batch_seq_scan(&mut self, plan: &BatchSeqScan) {
+        self.add(plan.logical().table_desc().table_id);
+    }
+
+    fn visit_logical_scan(&mut self, plan: &LogicalScan) {
+        self.add(plan.table_desc().table_id);
+    }
+
+    fn visit_logical_source(&mut self, plan: &LogicalSource) {
+        self.add(TableId::new(plan.source_catalog.id));
+    }
+}
+
+#[derive(Default)]
+struct StatefulOperatorCounter {
+    keyed_state_count: usize,
+    simple_agg_count: usize,
+}
+
+impl PlanVisitor<()> for StatefulOperatorCounter {
+    fn visit_stream_hash_agg(&mut self, plan: &StreamHashAgg) {
+        self.keyed_state_count += 1;
+        self.visit(plan.input());
+    }
+
+    fn visit_stream_hash_join(&mut self, plan: &StreamHashJoin) {
+        self.keyed_state_count += 1;
+        plan.inputs()
+            .into_iter()
+            .for_each(|input| self.visit(input));
+    }
+
+    fn visit_stream_simple_agg(&mut self, plan: &StreamSimpleAgg) {
+        self.simple_agg_count += 1;
+        self.visit(plan.input());
     }
 }
 
@@ -203,7 +989,7 @@ impl PlanTreeNodeUnary for StreamMaterialize {
     }
 
     fn clone_with_input(&self, input: PlanRef) -> Self {
-        let new = Self::new(input, self.table().clone());
+        let new = Self::new(input, self.table.clone());
         assert_eq!(new.plan_base().schema, self.plan_base().schema);
         assert_eq!(new.plan_base().pk_indices, self.plan_base().pk_indices);
         new
@@ -252,3 +1038,740 @@ impl ToStreamProst for StreamMaterialize {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{ColumnDesc, Field, TableDesc};
+    use risingwave_common::types::DataType;
+
+    use super::*;
+    use crate::expr::InputRef;
+    use crate::optimizer::plan_node::{
+        LogicalAgg, LogicalProject, LogicalScan, LogicalValues, PlanAggCall, StreamProject,
+        StreamTableScan,
+    };
+    use crate::optimizer::property::{Direction, FieldOrder};
+    use crate::session::OptimizerContext;
+
+    /// Builds a stream input scanning a table of `fields` keyed by the first of them, with the
+    /// given distribution.
+    async fn keyed_input(fields: Vec<Field>, dist: Distribution) -> PlanRef {
+        let ctx = OptimizerContext::mock().await;
+        let columns = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| ColumnDesc::new_atomic(field.data_type(), &field.name, i as i32))
+            .collect_vec();
+        let table_desc = TableDesc {
+            table_id: TableId::new(1),
+            pk: vec![OrderedColumnDesc {
+                column_desc: columns[0].clone(),
+                order: OrderType::Ascending,
+            }],
+            columns,
+        };
+        let required_col_idx = (0..fields.len()).collect();
+        let scan = LogicalScan::new("t".to_string(), required_col_idx, Rc::new(table_desc), ctx);
+        let scan = StreamTableScan::new(scan);
+        StreamExchange::new(scan.into(), dist).into()
+    }
+
+    /// Builds a stream input over `v1, v2, v3` keyed by `v1`, with the given distribution.
+    async fn input_with_dist(dist: Distribution) -> PlanRef {
+        let fields = vec![
+            Field::with_name(DataType::Int32, "v1"),
+            Field::with_name(DataType::Int32, "v2"),
+            Field::with_name(DataType::Int32, "v3"),
+        ];
+        keyed_input(fields, dist).await
+    }
+
+    /// Builds a materialize over all columns of `input`, without any order or options.
+    fn materialize(input: PlanRef) -> Result<StreamMaterialize> {
+        let mut user_cols = FixedBitSet::with_capacity(input.schema().len());
+        user_cols.insert_range(..);
+        StreamMaterialize::create(
+            input,
+            "mv".to_string(),
+            Order::any().clone(),
+            user_cols,
+            MaterializeOptions::default(),
+        )
+    }
+
+    /// Builds a materialize over `v1, v2, v3` with the given input distribution.
+    async fn materialize_with_dist(dist: Distribution) -> StreamMaterialize {
+        materialize(input_with_dist(dist).await).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_partition_alignment() {
+        let mv = materialize_with_dist(Distribution::HashShard(vec![0, 1])).await;
+        assert_eq!(mv.partition_alignment(&[1, 0]), 1.0);
+        assert_eq!(mv.partition_alignment(&[2]), 0.0);
+        assert_eq!(mv.partition_alignment(&[0]), 0.5);
+
+        let mv = materialize_with_dist(Distribution::Single).await;
+        assert_eq!(mv.partition_alignment(&[0]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_reuse_table_id() {
+        let mv = materialize_with_dist(Distribution::Single).await;
+        assert_eq!(mv.table().id(), TableId::placeholder());
+
+        let input = mv.input();
+        let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b111]);
+        let mv = StreamMaterialize::create(
+            input.clone(),
+            "mv".to_string(),
+            Order::any().clone(),
+            user_cols.clone(),
+            MaterializeOptions {
+                reuse_table_id: Some(TableId::new(42)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(mv.table().id(), TableId::new(42));
+
+        assert_eq!(
+            StreamMaterialize::create(
+                input.clone(),
+                "mv".to_string(),
+                Order::any().clone(),
+                user_cols,
+                MaterializeOptions {
+                    reuse_table_id: Some(TableId::placeholder()),
+                    ..Default::default()
+                }
+            )
+            .unwrap_err(),
+            MaterializeError::PlaceholderTableId(TableId::placeholder(), "mv".to_string())
+        );
+
+        // The last id meta may allocate can be reused, the first reserved one cannot.
+        let create = |id| {
+            StreamMaterialize::create(
+                input.clone(),
+                "mv".to_string(),
+                Order::any().clone(),
+                FixedBitSet::with_capacity_and_blocks(3, [0b111]),
+                MaterializeOptions {
+                    reuse_table_id: Some(TableId::new(id)),
+                    ..Default::default()
+                },
+            )
+        };
+        let mv = create(RESERVED_TABLE_ID_START - 1).unwrap();
+        assert_eq!(mv.table().id(), TableId::new(i32::MAX as u32));
+        assert_eq!(
+            create(RESERVED_TABLE_ID_START).unwrap_err(),
+            MaterializeError::ReservedTableId(
+                TableId::new(RESERVED_TABLE_ID_START),
+                "mv".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_with_mirror() {
+        let input = materialize_with_dist(Distribution::HashShard(vec![0]))
+            .await
+            .input();
+        let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b111]);
+        let order_by_v2 = Order::new(vec![FieldOrder {
+            index: 1,
+            direct: Direction::Desc,
+        }]);
+        let (primary, mirror) = StreamMaterialize::create_with_mirror(
+            input.clone(),
+            "mv".to_string(),
+            "mv_by_v2".to_string(),
+            Order::any().clone(),
+            user_cols.clone(),
+            vec![1],
+            MaterializeOptions {
+                reuse_table_id: Some(TableId::new(42)),
+                description: Some("daily revenue".to_string()),
+                tie_break_by: Some(order_by_v2),
+            },
+        )
+        .unwrap();
+
+        assert!(!primary.table().is_mirror());
+        assert!(mirror.table().is_mirror());
+        assert_eq!(mirror.name(), "mv_by_v2");
+        assert_eq!(primary.table().id(), TableId::new(42));
+        assert_eq!(mirror.table().id(), TableId::placeholder());
+        assert_eq!(mirror.table().description(), Some("daily revenue"));
+        assert_eq!(mirror.table().pk_desc(), primary.table().pk_desc());
+        assert_eq!(mirror.output_order().to_string(), "[$1 DESC, $0 ASC]");
+        assert_eq!(primary.distribution(), &Distribution::HashShard(vec![0]));
+        assert_eq!(mirror.distribution(), &Distribution::HashShard(vec![1]));
+        assert_eq!(primary.input().id().0, mirror.input().inputs()[0].id().0);
+        assert!(format!("{}", mirror).ends_with(", mirror: true }"));
+
+        assert_eq!(
+            StreamMaterialize::create_with_mirror(
+                input.clone(),
+                "mv".to_string(),
+                "mv_mirror".to_string(),
+                Order::any().clone(),
+                user_cols.clone(),
+                vec![0],
+                MaterializeOptions::default(),
+            )
+            .unwrap_err(),
+            MaterializeError::MirrorSameDist("mv".to_string())
+        );
+        assert_eq!(
+            StreamMaterialize::create_with_mirror(
+                input.clone(),
+                "mv".to_string(),
+                "mv_mirror".to_string(),
+                Order::any().clone(),
+                user_cols.clone(),
+                vec![3],
+                MaterializeOptions::default(),
+            )
+            .unwrap_err(),
+            MaterializeError::InvalidMirrorDistKey(vec![3], "mv".to_string())
+        );
+        assert_eq!(
+            StreamMaterialize::create_with_mirror(
+                input,
+                "mv".to_string(),
+                "mv".to_string(),
+                Order::any().clone(),
+                user_cols,
+                vec![1],
+                MaterializeOptions::default(),
+            )
+            .unwrap_err(),
+            MaterializeError::MirrorSameName("mv".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_materialize_tables() {
+        let inner = materialize_with_dist(Distribution::HashShard(vec![0])).await;
+        let outer =
+            materialize(StreamExchange::new(inner.into(), Distribution::Single).into()).unwrap();
+
+        let mut rewriter = MaterializeTableRewriter::new(|table: &TableCatalog| {
+            let mut table = table.clone();
+            table.name = format!("tenant_{}", table.name);
+            table
+        });
+        let plan = rewriter.rewrite(outer.into());
+
+        let outer = plan.as_stream_materialize().unwrap();
+        assert_eq!(outer.name(), "tenant_mv");
+        let inner = outer.input().inputs()[0].clone();
+        assert_eq!(inner.as_stream_materialize().unwrap().name(), "tenant_mv");
+    }
+
+    #[tokio::test]
+    async fn test_supports_arrangement_backfill() {
+        let input = input_with_dist(Distribution::HashShard(vec![0])).await;
+        let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b111]);
+        let order_by = |index| {
+            Order::new(vec![FieldOrder {
+                index,
+                direct: Direction::Asc,
+            }])
+        };
+        let mv = StreamMaterialize::create(
+            input.clone(),
+            "mv".to_string(),
+            order_by(0),
+            user_cols.clone(),
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert!(mv.supports_arrangement_backfill());
+
+        let mv = StreamMaterialize::create(
+            input,
+            "mv".to_string(),
+            order_by(1),
+            user_cols,
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert!(!mv.supports_arrangement_backfill());
+
+        // The pk is `v1, v2, ..`, so only a key of `v1, v2` in this order is its prefix.
+        let order_by_v1_v2 = Order::new(vec![
+            FieldOrder {
+                index: 0,
+                direct: Direction::Asc,
+            },
+            FieldOrder {
+                index: 1,
+                direct: Direction::Asc,
+            },
+        ]);
+        for (dist_key, supported) in [(vec![0, 1], true), (vec![1, 0], false)] {
+            let input = input_with_dist(Distribution::HashShard(dist_key)).await;
+            let mv = StreamMaterialize::create(
+                input,
+                "mv".to_string(),
+                order_by_v1_v2.clone(),
+                FixedBitSet::with_capacity_and_blocks(3, [0b111]),
+                MaterializeOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(mv.supports_arrangement_backfill(), supported);
+        }
+
+        let mv = materialize_with_dist(Distribution::Single).await;
+        assert!(mv.supports_arrangement_backfill());
+        let mv = materialize_with_dist(Distribution::AnyShard).await;
+        assert!(mv.supports_arrangement_backfill());
+    }
+
+    #[tokio::test]
+    async fn test_warn_pk_of_all_columns() {
+        let mv = materialize_with_dist(Distribution::HashShard(vec![0])).await;
+        assert!(mv.ctx().notices().is_empty());
+
+        let fields = vec![Field::with_name(DataType::Int32, "v1")];
+        let input = keyed_input(fields.clone(), Distribution::HashShard(vec![0])).await;
+        let mv = materialize(input).unwrap();
+        assert_eq!(mv.ctx().notices().len(), 1);
+        assert!(mv.ctx().notices()[0].starts_with("the pk of materialized view mv covers all"));
+
+        // A mirror does not repeat the notice.
+        let input = keyed_input(fields.clone(), Distribution::AnyShard).await;
+        let user_cols = FixedBitSet::with_capacity_and_blocks(1, [0b1]);
+        let (primary, _) = StreamMaterialize::create_with_mirror(
+            input,
+            "mv".to_string(),
+            "mv_mirror".to_string(),
+            Order::any().clone(),
+            user_cols,
+            vec![0],
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(primary.ctx().notices().len(), 1);
+
+        // A single row, the group key of an aggregation or a row id alone is expected to be the
+        // whole pk.
+        let input = keyed_input(fields.clone(), Distribution::Single).await;
+        assert!(materialize(input).unwrap().ctx().notices().is_empty());
+        let input = input_with_dist(Distribution::HashShard(vec![0, 1, 2])).await;
+        let agg = StreamHashAgg::new(LogicalAgg::new(vec![], vec![], vec![0, 1, 2], input));
+        assert!(materialize(agg.into()).unwrap().ctx().notices().is_empty());
+        let input = keyed_input(fields, Distribution::HashShard(vec![0])).await;
+        let mv = StreamMaterialize::create(
+            input,
+            "mv".to_string(),
+            Order::any().clone(),
+            FixedBitSet::with_capacity(1),
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert!(mv.ctx().notices().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dist_key_out_of_range() {
+        let input = input_with_dist(Distribution::HashShard(vec![0, 3])).await;
+        let err = materialize(input).unwrap_err();
+        assert_eq!(
+            err,
+            MaterializeError::DistKeyOutOfRange(3, "mv".to_string(), 3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_columns() {
+        let mv = materialize_with_dist(Distribution::Single).await;
+        assert_eq!(mv.columns(), mv.table().columns());
+    }
+
+    #[tokio::test]
+    async fn test_description() {
+        let input = input_with_dist(Distribution::Single).await;
+        let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b111]);
+        let mv = StreamMaterialize::create(
+            input.clone(),
+            "mv".to_string(),
+            Order::any().clone(),
+            user_cols,
+            MaterializeOptions {
+                description: Some("today's \"revenue\"".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(mv.table().description(), Some("today's \"revenue\""));
+        assert!(format!("{}", mv).ends_with(r#", description: 'today''s "revenue"' }"#));
+
+        let mv = mv.clone_with_input(input);
+        assert_eq!(mv.table().description(), Some("today's \"revenue\""));
+    }
+
+    #[tokio::test]
+    async fn test_pk_tie_break() {
+        let input = input_with_dist(Distribution::HashShard(vec![0, 1, 2])).await;
+        let agg: PlanRef =
+            StreamHashAgg::new(LogicalAgg::new(vec![], vec![], vec![0, 1, 2], input)).into();
+        let exprs = (0..3)
+            .rev()
+            .map(|i| InputRef::new(i, DataType::Int32).into())
+            .collect();
+        let project: PlanRef =
+            StreamProject::new(LogicalProject::new(agg, exprs, vec![None; 3])).into();
+        assert_eq!(project.pk_indices(), &[2, 1, 0]);
+
+        let create = || {
+            let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b111]);
+            let order_by_v2 = Order::new(vec![FieldOrder {
+                index: 1,
+                direct: Direction::Desc,
+            }]);
+            StreamMaterialize::create(
+                project.clone(),
+                "mv".to_string(),
+                order_by_v2,
+                user_cols,
+                MaterializeOptions::default(),
+            )
+            .unwrap()
+        };
+        let mv = create();
+        let pk_column_ids = mv
+            .table()
+            .pk_desc()
+            .iter()
+            .map(|c| c.column_desc.column_id.get_id())
+            .collect_vec();
+        assert_eq!(pk_column_ids, vec![1, 0, 2]);
+        let recreated = create();
+        assert_eq!(mv.table().pk_desc(), recreated.table().pk_desc());
+        // Reads scan by the same prefix of the pk after recreation.
+        assert_eq!(
+            mv.table().read_prefix_len_hint(),
+            recreated.table().read_prefix_len_hint()
+        );
+        assert_eq!(mv.table().effective_read_prefix_len(), 3);
+        assert_eq!(recreated.table().effective_read_prefix_len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_input() {
+        let input = input_with_dist(Distribution::Broadcast).await;
+        let err = materialize(input).unwrap_err();
+        assert_eq!(err, MaterializeError::BroadcastInput("mv".to_string()));
+        assert_eq!(
+            RwError::from(err).to_string(),
+            "internal error: materialized view mv cannot be created over a broadcast input"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_shard_key() {
+        let input = input_with_dist(Distribution::HashShard(vec![])).await;
+        let err = materialize(input).unwrap_err();
+        assert_eq!(err, MaterializeError::EmptyDistKey("mv".to_string()));
+        assert_eq!(
+            RwError::from(err).to_string(),
+            "Invalid input syntax: materialized view mv cannot be distributed by an empty key"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_column_names() {
+        let fields = vec![
+            Field::with_name(DataType::Int32, "x"),
+            Field::with_name(DataType::Int32, "x"),
+            Field::with_name(DataType::Int32, "v3"),
+        ];
+        let input = keyed_input(fields, Distribution::Single).await;
+
+        let err = materialize(input.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            MaterializeError::DuplicateColumnName("x".to_string(), "mv".to_string())
+        );
+
+        // A hidden `x` would still make `x` ambiguous to bind against the materialized view.
+        let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b101]);
+        let err = StreamMaterialize::create(
+            input,
+            "mv".to_string(),
+            Order::any().clone(),
+            user_cols,
+            MaterializeOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            MaterializeError::DuplicateColumnName("x".to_string(), "mv".to_string())
+        );
+
+        // Generated names may repeat, e.g. the row ids of both sides of a join.
+        let fields = vec![
+            Field::with_name(DataType::Int32, "x"),
+            Field::with_name(DataType::Int64, "_row_id#0"),
+            Field::with_name(DataType::Int64, "_row_id#0"),
+            Field::with_name(DataType::Int64, "agg#0"),
+            Field::with_name(DataType::Int64, "agg#0"),
+        ];
+        let input = keyed_input(fields, Distribution::Single).await;
+        let user_cols = FixedBitSet::with_capacity_and_blocks(5, [0b1]);
+        let mv = StreamMaterialize::create(
+            input,
+            "mv".to_string(),
+            Order::any().clone(),
+            user_cols,
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(mv.columns()[1].name(), "_row_id#0");
+        assert_eq!(mv.columns()[2].name(), "_row_id#1");
+        assert!(mv.columns()[4].is_hidden);
+    }
+
+    #[tokio::test]
+    async fn test_clone_with_input_shares_table() {
+        let mv = materialize_with_dist(Distribution::Single).await;
+        let cloned = mv.clone_with_input(mv.input());
+        assert!(Rc::ptr_eq(&mv.table, &cloned.table));
+        assert_eq!(cloned.table(), mv.table());
+        assert_eq!(format!("{}", cloned), format!("{}", mv));
+    }
+
+    #[tokio::test]
+    async fn test_from_catalog() {
+        let mv = materialize_with_dist(Distribution::HashShard(vec![0])).await;
+        let rebuilt = StreamMaterialize::from_catalog(mv.input(), mv.table().clone()).unwrap();
+        assert_eq!(rebuilt.table(), mv.table());
+        assert_eq!(rebuilt.distribution(), mv.distribution());
+        assert_eq!(format!("{}", rebuilt), format!("{}", mv));
+
+        let mut table = mv.table().clone();
+        table.columns.pop();
+        let err = StreamMaterialize::from_catalog(mv.input(), table).unwrap_err();
+        assert_eq!(
+            err,
+            MaterializeError::ColumnMismatch(
+                "mv".to_string(),
+                vec!["v1".to_string(), "v2".to_string(), "v3".to_string()]
+            )
+        );
+
+        let input = input_with_dist(Distribution::HashShard(vec![0, 1, 2])).await;
+        let agg: PlanRef =
+            StreamHashAgg::new(LogicalAgg::new(vec![], vec![], vec![0, 1, 2], input)).into();
+        // The pk of the materialize only has `v1`, while the stream key of the agg is all columns.
+        let table = mv.table().clone();
+        let err = StreamMaterialize::from_catalog(agg, table).unwrap_err();
+        assert_eq!(
+            err,
+            MaterializeError::StreamKeyNotInPk("v2".to_string(), "mv".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_column_names() {
+        let fields = vec![
+            Field::with_name(DataType::Int32, "weird name"),
+            Field::with_name(DataType::Int32, r#"say "hi""#),
+            Field::with_name(DataType::Int32, "agg#0"),
+            Field::with_name(DataType::Int32, "line\nbreak"),
+        ];
+        let input = keyed_input(fields, Distribution::HashShard(vec![0])).await;
+        let user_cols = FixedBitSet::with_capacity_and_blocks(4, [0b1011]);
+        let mv = StreamMaterialize::create(
+            input,
+            "mv".to_string(),
+            Order::any().clone(),
+            user_cols,
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            format!("{}", mv),
+            r#"StreamMaterialize { columns: ["weird name", "say ""hi""", "agg#0"(hidden), "line\nbreak"], pk_columns: ["weird name"], dist_key: ["weird name"] }"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_output_order() {
+        let input = input_with_dist(Distribution::HashShard(vec![0, 1, 2])).await;
+        let agg: PlanRef =
+            StreamHashAgg::new(LogicalAgg::new(vec![], vec![], vec![0, 1, 2], input)).into();
+        let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b111]);
+        let order_by_v3 = Order::new(vec![FieldOrder {
+            index: 2,
+            direct: Direction::Desc,
+        }]);
+        let mv = StreamMaterialize::create(
+            agg,
+            "mv".to_string(),
+            order_by_v3,
+            user_cols,
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(mv.output_order().to_string(), "[$2 DESC, $0 ASC, $1 ASC]");
+        assert!(mv.order().field_order.is_empty());
+        assert_eq!(
+            mv.clone_with_input(mv.input()).output_order().to_string(),
+            "[$2 DESC, $0 ASC, $1 ASC]"
+        );
+
+        // A catalog whose pk refers to a missing column only orders by the pk ahead of it.
+        let mut table = mv.table().clone();
+        table.columns.remove(0);
+        let mv = StreamMaterialize::new(mv.input(), Rc::new(table));
+        assert_eq!(mv.output_order().to_string(), "[$1 DESC]");
+    }
+
+    #[tokio::test]
+    async fn test_tie_break() {
+        let input = input_with_dist(Distribution::HashShard(vec![0, 1, 2])).await;
+        let agg: PlanRef =
+            StreamHashAgg::new(LogicalAgg::new(vec![], vec![], vec![0, 1, 2], input)).into();
+        let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b111]);
+        let order_by_v3 = Order::new(vec![FieldOrder {
+            index: 2,
+            direct: Direction::Desc,
+        }]);
+        // Rows tied on `v3` are ordered by `v2` descending, then by the rest of the stream key.
+        let tie_break_by = Order::new(vec![
+            FieldOrder {
+                index: 2,
+                direct: Direction::Asc,
+            },
+            FieldOrder {
+                index: 1,
+                direct: Direction::Desc,
+            },
+        ]);
+        let mv = StreamMaterialize::create(
+            agg.clone(),
+            "mv".to_string(),
+            order_by_v3.clone(),
+            user_cols.clone(),
+            MaterializeOptions {
+                tie_break_by: Some(tie_break_by),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(mv.output_order().to_string(), "[$2 DESC, $1 DESC, $0 ASC]");
+
+        let mv = StreamMaterialize::create(
+            agg,
+            "mv".to_string(),
+            order_by_v3,
+            user_cols,
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(mv.output_order().to_string(), "[$2 DESC, $0 ASC, $1 ASC]");
+    }
+
+    #[tokio::test]
+    async fn test_to_table_prost() {
+        let input = input_with_dist(Distribution::HashShard(vec![0])).await;
+        let user_cols = FixedBitSet::with_capacity_and_blocks(3, [0b111]);
+        let order_by = Order::new(vec![
+            FieldOrder {
+                index: 1,
+                direct: Direction::Desc,
+            },
+            FieldOrder {
+                index: 0,
+                direct: Direction::Asc,
+            },
+        ]);
+        let mv = StreamMaterialize::create(
+            input,
+            "mv".to_string(),
+            order_by,
+            user_cols,
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        let table = mv.to_table_prost(1, 2);
+        assert_eq!(table.schema_id, 1);
+        assert_eq!(table.database_id, 2);
+        assert_eq!(table.columns.len(), 3);
+        assert_eq!(table.pk_column_ids, vec![1, 0]);
+        assert_eq!(
+            table.pk_orders,
+            mv.table()
+                .pk_desc()
+                .iter()
+                .map(|c| c.order.to_prost() as i32)
+                .collect_vec()
+        );
+        assert_eq!(TableCatalog::from(table), *mv.table());
+    }
+
+    #[tokio::test]
+    async fn test_empty_pk() {
+        let ctx = OptimizerContext::mock().await;
+        let fields = vec![Field::with_name(DataType::Int32, "v1")];
+        let values = LogicalValues::new(vec![], Schema { fields }, ctx);
+        let input: PlanRef = StreamExchange::new(values.into(), Distribution::Single).into();
+        assert!(input.pk_indices().is_empty());
+        let err = materialize(input).unwrap_err();
+        assert_eq!(err, MaterializeError::EmptyPk("mv".to_string()));
+        assert_eq!(
+            RwError::from(err).to_string(),
+            "Invalid input syntax: materialized view mv has no stream key to derive its pk from"
+        );
+
+        // Hidden columns alone are still stored.
+        let input = input_with_dist(Distribution::Single).await;
+        let mv = StreamMaterialize::create(
+            input,
+            "mv".to_string(),
+            Order::any().clone(),
+            FixedBitSet::with_capacity(3),
+            MaterializeOptions::default(),
+        )
+        .unwrap();
+        assert!(mv.columns().iter().all(|c| c.is_hidden));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_state_class() {
+        let mv = materialize_with_dist(Distribution::Single).await;
+        assert_eq!(mv.estimate_state_class(), StateSizeClass::Medium);
+
+        let input = input_with_dist(Distribution::Single).await;
+        let simple_agg = StreamSimpleAgg::new(LogicalAgg::new(
+            vec![PlanAggCall::count_star()],
+            vec![None],
+            vec![],
+            input,
+        ));
+        assert_eq!(
+            materialize(simple_agg.into())
+                .unwrap()
+                .estimate_state_class(),
+            StateSizeClass::Small
+        );
+
+        let input = input_with_dist(Distribution::HashShard(vec![0])).await;
+        let hash_agg = StreamHashAgg::new(LogicalAgg::new(
+            vec![PlanAggCall::count_star()],
+            vec![None],
+            vec![0],
+            input,
+        ));
+        assert_eq!(
+            materialize(hash_agg.into()).unwrap().estimate_state_class(),
+            StateSizeClass::Large
+        );
+    }
+}
@@ -53,6 +53,12 @@ impl StreamProject {
         );
         StreamProject { base, logical }
     }
+
+    /// Get a reference to the stream project's logical.
+    #[must_use]
+    pub fn logical(&self) -> &LogicalProject {
+        &self.logical
+    }
 }
 
 impl PlanTreeNodeUnary for StreamProject {
@@ -47,6 +47,12 @@ impl StreamSource {
             .map(|f| f.name.clone())
             .collect()
     }
+
+    /// Get a reference to the stream source's logical.
+    #[must_use]
+    pub fn logical(&self) -> &LogicalSource {
+        &self.logical
+    }
 }
 
 impl_plan_tree_node_for_leaf! { StreamSource }
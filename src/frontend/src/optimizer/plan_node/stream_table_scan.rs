@@ -55,6 +55,12 @@ impl StreamTableScan {
     pub fn table_name(&self) -> &str {
         self.logical.table_name()
     }
+
+    /// Get a reference to the stream table scan's logical.
+    #[must_use]
+    pub fn logical(&self) -> &LogicalScan {
+        &self.logical
+    }
 }
 impl_plan_tree_node_for_leaf! { StreamTableScan }
 
@@ -124,6 +124,15 @@ impl From<Direction> for OrderType {
     }
 }
 
+impl From<OrderType> for Direction {
+    fn from(order_type: OrderType) -> Self {
+        match order_type {
+            OrderType::Ascending => Direction::Asc,
+            OrderType::Descending => Direction::Desc,
+        }
+    }
+}
+
 impl fmt::Display for Direction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
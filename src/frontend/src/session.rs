@@ -21,7 +21,7 @@ use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use pgwire::pg_response::PgResponse;
 use pgwire::pg_server::{Session, SessionManager};
 use risingwave_common::config::FrontendConfig;
@@ -49,6 +49,12 @@ pub struct OptimizerContext {
     pub session_ctx: Arc<SessionImpl>,
     // We use `AtomicI32` here because  `Arc<T>` implements `Send` only when `T: Send + Sync`.
     pub next_id: AtomicI32,
+    /// Non-fatal warnings about the plan collected during optimization.
+    pub notices: Mutex<Vec<String>>,
+    /// The `WITH` options of the statement being planned.
+    pub with_options: HashMap<String, String>,
+    /// Whether the plan is being explained with `EXPLAIN VERBOSE`.
+    pub explain_verbose: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -77,6 +83,26 @@ impl OptimizerContextRef {
         let next_id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
         PlanNodeId(next_id)
     }
+
+    /// Record a non-fatal warning about the plan to be reported to the user.
+    pub fn warn_to_user(&self, notice: impl Into<String>) {
+        self.inner.notices.lock().push(notice.into());
+    }
+
+    /// Get the warnings recorded so far.
+    pub fn notices(&self) -> Vec<String> {
+        self.inner.notices.lock().clone()
+    }
+
+    /// Get the `WITH` options of the statement being planned.
+    pub fn with_options(&self) -> &HashMap<String, String> {
+        &self.inner.with_options
+    }
+
+    /// Whether the plan is being explained with `EXPLAIN VERBOSE`.
+    pub fn is_explain_verbose(&self) -> bool {
+        self.inner.explain_verbose
+    }
 }
 
 impl OptimizerContext {
@@ -84,6 +110,9 @@ impl OptimizerContext {
         Self {
             session_ctx,
             next_id: AtomicI32::new(0),
+            notices: Mutex::new(vec![]),
+            with_options: HashMap::new(),
+            explain_verbose: false,
         }
     }
 
@@ -93,6 +122,9 @@ impl OptimizerContext {
         Self {
             session_ctx: Arc::new(SessionImpl::mock()),
             next_id: AtomicI32::new(0),
+            notices: Mutex::new(vec![]),
+            with_options: HashMap::new(),
+            explain_verbose: false,
         }
         .into()
     }
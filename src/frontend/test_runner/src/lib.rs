@@ -233,8 +233,13 @@ impl TestCase {
                     }
                     result = Some(ret);
                 }
-                Statement::CreateTable { name, columns, .. } => {
-                    create_table::handle_create_table(context, name, columns).await?;
+                Statement::CreateTable {
+                    name,
+                    columns,
+                    with_options,
+                    ..
+                } => {
+                    create_table::handle_create_table(context, name, columns, with_options).await?;
                 }
                 Statement::CreateSource {
                     is_materialized,
@@ -247,9 +252,12 @@ impl TestCase {
                     or_replace: false,
                     name,
                     query,
+                    with_options,
+                    if_not_exists,
                     ..
                 } => {
-                    create_mv::handle_create_mv(context, name, query).await?;
+                    create_mv::handle_create_mv(context, name, query, with_options, if_not_exists)
+                        .await?;
                 }
                 Statement::Drop(drop_statement) => {
                     let table_object_name = ObjectName(vec![drop_statement.name]);
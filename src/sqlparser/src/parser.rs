@@ -1407,6 +1407,13 @@ impl Parser {
     ) -> Result<Statement, ParserError> {
         // Many dialects support `OR ALTER` right after `CREATE`, but we don't (yet).
         // ANSI SQL and Postgres support RECURSIVE here, but we don't support it either.
+        let if_not_exists = self.parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        if if_not_exists && or_replace {
+            return parser_err!("Cannot specify both OR REPLACE and IF NOT EXISTS");
+        }
+        if if_not_exists && !materialized {
+            return parser_err!("IF NOT EXISTS is only supported for MATERIALIZED VIEW");
+        }
         let name = self.parse_object_name()?;
         let columns = self.parse_parenthesized_column_list(Optional)?;
         let with_options = self.parse_options(Keyword::WITH)?;
@@ -1419,6 +1426,7 @@ impl Parser {
             query,
             materialized,
             or_replace,
+            if_not_exists,
             with_options,
         })
     }
@@ -2766,12 +2766,14 @@ fn parse_create_view() {
             query,
             or_replace,
             materialized,
+            if_not_exists,
             with_options,
         } => {
             assert_eq!("myschema.myview", name.to_string());
             assert_eq!(Vec::<Ident>::new(), columns);
             assert_eq!("SELECT foo FROM bar", query.to_string());
             assert!(!materialized);
+            assert!(!if_not_exists);
             assert!(!or_replace);
             assert_eq!(with_options, vec![]);
         }
@@ -2813,12 +2815,14 @@ fn parse_create_view_with_columns() {
             with_options,
             query,
             materialized,
+            if_not_exists,
         } => {
             assert_eq!("v", name.to_string());
             assert_eq!(columns, vec![Ident::new("has"), Ident::new("cols")]);
             assert_eq!(with_options, vec![]);
             assert_eq!("SELECT 1, 2", query.to_string());
             assert!(!materialized);
+            assert!(!if_not_exists);
             assert!(!or_replace)
         }
         _ => unreachable!(),
@@ -2835,12 +2839,14 @@ fn parse_create_or_replace_view() {
             with_options,
             query,
             materialized,
+            if_not_exists,
         } => {
             assert_eq!("v", name.to_string());
             assert_eq!(columns, vec![]);
             assert_eq!(with_options, vec![]);
             assert_eq!("SELECT 1", query.to_string());
             assert!(!materialized);
+            assert!(!if_not_exists);
             assert!(or_replace)
         }
         _ => unreachable!(),
@@ -2862,18 +2868,54 @@ fn parse_create_or_replace_materialized_view() {
             with_options,
             query,
             materialized,
+            if_not_exists,
         } => {
             assert_eq!("v", name.to_string());
             assert_eq!(columns, vec![]);
             assert_eq!(with_options, vec![]);
             assert_eq!("SELECT 1", query.to_string());
             assert!(materialized);
+            assert!(!if_not_exists);
             assert!(or_replace)
         }
         _ => unreachable!(),
     }
 }
 
+#[test]
+fn parse_create_materialized_view_if_not_exists() {
+    let sql = "CREATE MATERIALIZED VIEW IF NOT EXISTS myview AS SELECT foo FROM bar";
+    match verified_stmt(sql) {
+        Statement::CreateView {
+            name,
+            materialized,
+            if_not_exists,
+            ..
+        } => {
+            assert_eq!("myview", name.to_string());
+            assert!(materialized);
+            assert!(if_not_exists);
+        }
+        _ => unreachable!(),
+    }
+
+    let res = parse_sql_statements(
+        "CREATE OR REPLACE MATERIALIZED VIEW IF NOT EXISTS myview AS SELECT foo FROM bar",
+    );
+    assert_eq!(
+        ParserError::ParserError("Cannot specify both OR REPLACE and IF NOT EXISTS".to_string()),
+        res.unwrap_err()
+    );
+
+    let res = parse_sql_statements("CREATE VIEW IF NOT EXISTS myview AS SELECT foo FROM bar");
+    assert_eq!(
+        ParserError::ParserError(
+            "IF NOT EXISTS is only supported for MATERIALIZED VIEW".to_string()
+        ),
+        res.unwrap_err()
+    );
+}
+
 #[test]
 fn parse_create_materialized_view() {
     let sql = "CREATE MATERIALIZED VIEW myschema.myview AS SELECT foo FROM bar";
@@ -2884,12 +2926,14 @@ fn parse_create_materialized_view() {
             columns,
             query,
             materialized,
+            if_not_exists,
             with_options,
         } => {
             assert_eq!("myschema.myview", name.to_string());
             assert_eq!(Vec::<Ident>::new(), columns);
             assert_eq!("SELECT foo FROM bar", query.to_string());
             assert!(materialized);
+            assert!(!if_not_exists);
             assert_eq!(with_options, vec![]);
             assert!(!or_replace);
         }
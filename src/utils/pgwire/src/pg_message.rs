@@ -110,6 +110,7 @@ pub enum BeMessage<'a> {
     ReadyForQuery,
     RowDescription(&'a [PgFieldDescriptor]),
     ErrorResponse(Box<dyn std::error::Error + Send + Sync>),
+    NoticeResponse(&'a str),
 }
 
 #[derive(Debug)]
@@ -301,6 +302,24 @@ impl<'a> BeMessage<'a> {
                 })
                 .unwrap();
             }
+
+            BeMessage::NoticeResponse(notice) => {
+                // 'N' signalizes NoticeResponse messages, which share the fields of ErrorResponse.
+                buf.put_u8(b'N');
+                write_body(buf, |buf| {
+                    buf.put_u8(b'S'); // severity
+                    write_cstr(buf, &Bytes::from("NOTICE"))?;
+
+                    buf.put_u8(b'C'); // SQLSTATE error code
+                    write_cstr(buf, &Bytes::from("00000"))?;
+
+                    buf.put_u8(b'M'); // the message
+                    write_cstr(buf, notice.as_bytes())?;
+
+                    buf.put_u8(0); // terminator
+                    Ok(())
+                })?;
+            }
         }
 
         Ok(())
@@ -133,6 +133,9 @@ where
         let process_res = session.run_statement(query.get_sql()).await;
         match process_res {
             Ok(res) => {
+                for notice in res.get_notices() {
+                    self.write_message_no_flush(&BeMessage::NoticeResponse(notice))?;
+                }
                 if res.is_empty() {
                     self.write_message_no_flush(&BeMessage::EmptyQueryResponse)?;
                 } else if res.is_query() {
@@ -60,6 +60,8 @@ pub struct PgResponse {
 
     values: Vec<Row>,
     row_desc: Vec<PgFieldDescriptor>,
+    /// Non-fatal warnings sent to the client ahead of the result.
+    notices: Vec<String>,
 }
 
 impl StatementType {
@@ -89,9 +91,17 @@ impl PgResponse {
             row_cnt,
             values,
             row_desc,
+            notices: vec![],
         }
     }
 
+    /// Attach `notices` to be sent to the client ahead of the result.
+    #[must_use]
+    pub fn with_notices(mut self, notices: Vec<String>) -> Self {
+        self.notices = notices;
+        self
+    }
+
     pub fn empty_result(stmt_type: StatementType) -> Self {
         Self::new(stmt_type, 0, vec![], vec![])
     }
@@ -118,6 +128,10 @@ impl PgResponse {
         self.stmt_type == StatementType::EMPTY
     }
 
+    pub fn get_notices(&self) -> &[String] {
+        &self.notices
+    }
+
     pub fn get_row_desc(&self) -> Vec<PgFieldDescriptor> {
         self.row_desc.clone()
     }